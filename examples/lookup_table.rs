@@ -0,0 +1,33 @@
+// Copyright (c) 2025 Jan Holthuis <jan.holthuis@rub.de>
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Precomputes the position lookup table for a timecode format and writes it to a file.
+//!
+//! You can run this using:
+//!
+//! ```bash
+//! $ cargo run --example lookup_table -- serato_cd /path/to/serato_cd.lut
+//! ```
+//!
+//! The file can be loaded with `LookupTable::read_from`.
+
+use std::{env, fs::File, io::BufWriter};
+use vinylla::{find_format, LookupTable};
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let name = args.next().expect("No format given");
+    let path = args.next().expect("No file given");
+
+    let info = find_format(&name).expect("Unknown format");
+    let table = LookupTable::for_format(info.format);
+
+    let file = File::create(&path).unwrap();
+    table.write_to(BufWriter::new(file)).unwrap();
+    println!("Wrote lookup table for {} to {}", info.name, path);
+}
@@ -55,8 +55,12 @@ fn main() {
             None => return,
             Some(s) => s,
         };
-        if let Some((bit, position)) = timecode.process_channels(left, right) {
-            println!("{:10}: Bit {} => Position {:?}", i, bit as u8, position);
+        let status = timecode.process_channels(left, right);
+        if let Some(bit) = status.bit {
+            println!(
+                "{:10}: Bit {} => Position {:?}",
+                i, bit as u8, status.position
+            );
             i += 1;
         }
     }
@@ -78,8 +78,12 @@ fn main() {
             continue;
         }
         samples_read = true;
-        if let Some((bit, position)) = timecode.process_channels(left, right) {
-            println!("{:10}: Bit {} => Position {:?}", i, bit as u8, position);
+        let status = timecode.process_channels(left, right);
+        if let Some(bit) = status.bit {
+            println!(
+                "{:10}: Bit {} => Position {:?}",
+                i, bit as u8, status.position
+            );
         }
 
         visualizer.draw_sample(&mut pixels, PIXEL_SIZE, left, right);
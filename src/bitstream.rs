@@ -8,8 +8,387 @@
 
 //! The [`Bitstream` struct](Bitstream) processes bits and maps them to positions.
 
-use crate::{bits, lfsr::FibonacciLfsr};
-use std::collections::HashMap;
+use crate::{
+    bits,
+    format::{FormatError, TimecodeFormat},
+    lfsr::FibonacciLfsr,
+};
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    io::{self, Read},
+    sync::{Arc, Mutex, OnceLock, Weak},
+    thread,
+};
+
+/// The LFSR parameters that a lookup table was built from.
+type LookupTableKey = (usize, u32, u32);
+
+/// A lookup table that may still be under construction.
+type SharedLookupTable = OnceLock<LookupTable>;
+
+/// Lookup tables that are currently in use.
+///
+/// Only weak references are stored, so that a table is freed as soon as the last decoder using
+/// it is dropped.
+static LOOKUP_TABLES: Mutex<Vec<(LookupTableKey, Weak<SharedLookupTable>)>> =
+    Mutex::new(Vec::new());
+
+/// Maps LFSR states to positions.
+///
+/// The table is a dense array that is indexed by the state. All states of a maximal-length LFSR
+/// except zero are reachable, so almost all of the entries are used and the table needs 4 bytes
+/// per state (e.g. 4 MiB for a 20-bit LFSR).
+///
+/// Building the table requires clocking the LFSR through its whole period. To avoid that on
+/// startup, a precomputed table can be stored with [`LookupTable::write_to`] and loaded with
+/// [`LookupTable::read_from`]:
+///
+/// ```no_run
+/// use std::{fs::File, io::BufReader};
+/// use vinylla::{LookupTable, Timecode, SERATO_CONTROL_CD_1_0_0};
+///
+/// // Created with `LookupTable::for_format(&SERATO_CONTROL_CD_1_0_0).write_to(file)`.
+/// let file = File::open("serato_cd.lut").unwrap();
+/// let table = LookupTable::read_from(BufReader::new(file)).unwrap();
+/// let timecode = Timecode::with_lookup_table(&SERATO_CONTROL_CD_1_0_0, table, 44100.0).unwrap();
+/// ```
+///
+/// Alternatively, the file can be embedded into the binary at build time with
+/// [`include_bytes!`] and read from the resulting byte slice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookupTable {
+    size: usize,
+    seed: u32,
+    taps: u32,
+    positions: Vec<u32>,
+}
+
+impl LookupTable {
+    /// Marks states that are not reached by the LFSR.
+    const NO_POSITION: u32 = u32::MAX;
+
+    /// Identifies files created by [`LookupTable::write_to`].
+    const MAGIC: [u8; 4] = *b"VLUT";
+
+    /// Version of the file layout.
+    const VERSION: u32 = 1;
+
+    /// Create the lookup table for the format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the format is invalid (see [`TimecodeFormat::validate`]).
+    pub fn for_format(format: &TimecodeFormat) -> Self {
+        Self::new(format.size, format.seed, format.taps)
+    }
+
+    /// Create a lookup table by clocking the LFSR through its whole period.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table is too large for the address space, i.e. for LFSRs with more than
+    /// 28 bits on 32-bit targets.
+    pub fn new(size: usize, seed: u32, taps: u32) -> Self {
+        let capacity = bits::mask(size);
+        let mut lfsr = FibonacciLfsr {
+            size,
+            state: seed,
+            taps,
+        };
+        let length = Self::length(size).expect("lookup table is too large for the address space");
+        let mut positions = vec![Self::NO_POSITION; length];
+        for i in 0..capacity {
+            positions[lfsr.state as usize] = i;
+            lfsr.advance();
+        }
+
+        Self {
+            size,
+            seed,
+            taps,
+            positions,
+        }
+    }
+
+    /// Returns a lookup table for the LFSR, which is only built if no other decoder currently
+    /// uses a table with the same parameters.
+    ///
+    /// If the table is currently being built on a background thread, this blocks until it's
+    /// finished.
+    pub(crate) fn shared(size: usize, seed: u32, taps: u32) -> Arc<SharedLookupTable> {
+        let (table, _) = Self::find_or_insert_shared(size, seed, taps);
+        table.get_or_init(|| Self::new(size, seed, taps));
+        table
+    }
+
+    /// Like [`LookupTable::shared`], but builds the table on a background thread instead of
+    /// blocking.
+    pub(crate) fn shared_in_background(
+        size: usize,
+        seed: u32,
+        taps: u32,
+    ) -> Arc<SharedLookupTable> {
+        let (table, inserted) = Self::find_or_insert_shared(size, seed, taps);
+        if inserted {
+            let table = Arc::clone(&table);
+            thread::spawn(move || {
+                table.get_or_init(|| Self::new(size, seed, taps));
+            });
+        }
+        table
+    }
+
+    /// Returns the shared table for the LFSR parameters and whether it was newly inserted, i.e.
+    /// still needs to be built.
+    fn find_or_insert_shared(size: usize, seed: u32, taps: u32) -> (Arc<SharedLookupTable>, bool) {
+        let key = (size, seed, taps);
+        let mut tables = LOOKUP_TABLES
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        tables.retain(|(_, table)| table.strong_count() > 0);
+        if let Some(table) = tables
+            .iter()
+            .find(|(table_key, _)| *table_key == key)
+            .and_then(|(_, table)| table.upgrade())
+        {
+            return (table, false);
+        }
+
+        let table = Arc::new(SharedLookupTable::new());
+        tables.push((key, Arc::downgrade(&table)));
+        (table, true)
+    }
+
+    /// Returns the number of entries of the table for an LFSR with `size` bits, or `None` if the
+    /// table doesn't fit into the address space.
+    fn length(size: usize) -> Option<usize> {
+        let length = u64::from(bits::mask(size)) + 1;
+        let bytes = length.checked_mul(std::mem::size_of::<u32>() as u64)?;
+        // Allocations are limited to `isize::MAX` bytes.
+        if bytes > isize::MAX as u64 {
+            return None;
+        }
+        usize::try_from(length).ok()
+    }
+
+    /// Returns the position of the state (if the LFSR reaches it).
+    pub fn get(&self, state: u32) -> Option<u32> {
+        self.positions
+            .get(state as usize)
+            .copied()
+            .filter(|&position| position != Self::NO_POSITION)
+    }
+
+    /// Returns `true` if the table was built for the format.
+    pub fn matches_format(&self, format: &TimecodeFormat) -> bool {
+        self.size == format.size && self.seed == format.seed && self.taps == format.taps
+    }
+
+    /// Write the table in a compact binary representation.
+    ///
+    /// The data starts with a header that contains the LFSR parameters and a checksum, followed
+    /// by the positions for all states. All values are stored as little-endian `u32`.
+    pub fn write_to<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        let data: Vec<u8> = self
+            .positions
+            .iter()
+            .flat_map(|position| position.to_le_bytes())
+            .collect();
+
+        writer.write_all(&Self::MAGIC)?;
+        for value in [
+            Self::VERSION,
+            self.size as u32,
+            self.seed,
+            self.taps,
+            adler32(&data),
+        ] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        writer.write_all(&data)?;
+        writer.flush()
+    }
+
+    /// Read a table that was written by [`LookupTable::write_to`].
+    ///
+    /// Memory for the table is only allocated while the data is read, so a corrupted header
+    /// can't cause huge allocations. Use [`Timecode::with_lookup_table`] to check that the
+    /// table matches the format.
+    ///
+    /// [`Timecode::with_lookup_table`]: crate::Timecode::with_lookup_table
+    pub fn read_from<R: io::Read>(mut reader: R) -> Result<Self, LookupTableError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != Self::MAGIC {
+            return Err(LookupTableError::InvalidMagic);
+        }
+
+        let mut read_u32 = || -> io::Result<u32> {
+            let mut bytes = [0; 4];
+            reader.read_exact(&mut bytes)?;
+            Ok(u32::from_le_bytes(bytes))
+        };
+        let version = read_u32()?;
+        if version != Self::VERSION {
+            return Err(LookupTableError::UnsupportedVersion(version));
+        }
+        let size = read_u32()? as usize;
+        let seed = read_u32()?;
+        let taps = read_u32()?;
+        let checksum = read_u32()?;
+        if !(TimecodeFormat::MIN_SIZE..=TimecodeFormat::MAX_SIZE).contains(&size) {
+            return Err(FormatError::InvalidSize { size }.into());
+        }
+
+        // The buffer grows while reading, so that a header with a large size doesn't allocate
+        // more memory than the data actually contains.
+        let length = Self::length(size).ok_or(LookupTableError::TooLarge(size))?
+            * std::mem::size_of::<u32>();
+        let mut data = Vec::new();
+        reader.take(length as u64).read_to_end(&mut data)?;
+        if data.len() != length {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        if adler32(&data) != checksum {
+            return Err(LookupTableError::ChecksumMismatch);
+        }
+
+        let positions = data
+            .chunks_exact(std::mem::size_of::<u32>())
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        let table = Self {
+            size,
+            seed,
+            taps,
+            positions,
+        };
+
+        // Checking all positions would take as long as building the table, so at least check
+        // that the first positions are where the LFSR puts them.
+        let lfsr = FibonacciLfsr {
+            size,
+            state: seed,
+            taps,
+        };
+        if table.get(seed) != Some(0) || table.get(lfsr.next_state()) != Some(1) {
+            return Err(LookupTableError::InvalidPositions);
+        }
+
+        Ok(table)
+    }
+
+    /// Returns the approximate heap memory used by the table in bytes.
+    #[cfg(test)]
+    pub fn memory_usage(&self) -> usize {
+        self.positions.capacity() * std::mem::size_of::<u32>()
+    }
+}
+
+/// Error returned when a [`LookupTable`] can't be loaded or used.
+#[derive(Debug)]
+pub enum LookupTableError {
+    /// Reading the table failed.
+    Io(io::Error),
+    /// The data is not a lookup table.
+    InvalidMagic,
+    /// The table was written by an incompatible version.
+    UnsupportedVersion(u32),
+    /// The LFSR parameters in the header or the timecode format are invalid.
+    InvalidFormat(FormatError),
+    /// The table for the LFSR size in the header doesn't fit into the address space.
+    TooLarge(usize),
+    /// The table data is corrupted.
+    ChecksumMismatch,
+    /// The positions in the table don't match the LFSR parameters.
+    InvalidPositions,
+    /// The table was built for a different format.
+    FormatMismatch,
+}
+
+impl fmt::Display for LookupTableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "failed to read lookup table: {}", error),
+            Self::InvalidMagic => write!(f, "data is not a lookup table"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported lookup table version {}", version)
+            }
+            Self::InvalidFormat(error) => write!(f, "invalid lookup table format: {}", error),
+            Self::TooLarge(size) => {
+                write!(f, "lookup table for a {}-bit LFSR is too large", size)
+            }
+            Self::ChecksumMismatch => write!(f, "lookup table checksum mismatch"),
+            Self::InvalidPositions => {
+                write!(f, "lookup table positions don't match the LFSR")
+            }
+            Self::FormatMismatch => write!(f, "lookup table was built for a different format"),
+        }
+    }
+}
+
+impl Error for LookupTableError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::InvalidFormat(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LookupTableError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<FormatError> for LookupTableError {
+    fn from(error: FormatError) -> Self {
+        Self::InvalidFormat(error)
+    }
+}
+
+/// Calculate the Adler-32 checksum of the data.
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65521;
+    // Maximum number of bytes that can be summed up before `b` could overflow.
+    const CHUNK_SIZE: usize = 5552;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(CHUNK_SIZE) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= MODULUS;
+        b %= MODULUS;
+    }
+    (b << 16) | a
+}
+
+/// A bit together with the confidence of the decision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoftBit {
+    /// The bit value.
+    pub value: bool,
+    /// How certain the bit value is, from 0.0 (the reading was right at the decision threshold)
+    /// to 1.0 (the reading was far away from the threshold).
+    pub confidence: f64,
+}
+
+impl SoftBit {
+    /// Create a bit that is known for certain.
+    #[cfg(test)]
+    pub fn certain(value: bool) -> Self {
+        Self {
+            value,
+            confidence: 1.0,
+        }
+    }
+}
 
 /// Maps a bitstream to a position in the underlying lookup table.
 ///
@@ -17,72 +396,151 @@ use std::collections::HashMap;
 /// can then be used to retrieve a position for some n-bit sequence.
 #[derive(Debug)]
 pub struct Bitstream {
-    lookup_table: HashMap<u32, u32>,
+    lookup_table: Arc<SharedLookupTable>,
     size: usize,
+    taps: u32,
     bitstream: u32,
+    raw_bitstream: u32,
     valid_bits: usize,
+    max_bit_errors: usize,
+    error_history: u32,
 }
 
 impl Bitstream {
+    /// Bits with a lower confidence are not trusted if they contradict the expected bit.
+    const MIN_CONFIDENCE: f64 = 0.5;
+
     /// Create a timecode bitstream using a LFSR with length `capacity`.
+    ///
+    /// The lookup table is shared with all other bitstreams that use the same LFSR parameters.
     pub fn new(size: usize, seed: u32, taps: u32) -> Self {
-        // Precompute lookup table
-        let capacity = 2u32.pow(size as u32) - 1;
-        let mut lfsr = FibonacciLfsr {
+        Self::with_lookup_table(size, seed, taps, LookupTable::shared(size, seed, taps))
+    }
+
+    /// Create a timecode bitstream whose lookup table is built on a background thread.
+    ///
+    /// Bits can be processed right away, but no positions are available until the lookup table
+    /// is ready.
+    pub fn new_deferred(size: usize, seed: u32, taps: u32) -> Self {
+        Self::with_lookup_table(
             size,
-            state: seed,
+            seed,
             taps,
-        };
-        let mut lookup_table = HashMap::with_capacity(capacity as usize);
-        for i in 0..capacity {
-            lookup_table.insert(lfsr.state, i);
-            lfsr.advance();
-        }
+            LookupTable::shared_in_background(size, seed, taps),
+        )
+    }
 
+    /// Create a timecode bitstream that uses a precomputed lookup table.
+    ///
+    /// The table is not shared with other bitstreams, because a loaded table is only checked
+    /// partially (see [`LookupTable::read_from`]).
+    pub fn from_lookup_table(lookup_table: LookupTable) -> Self {
+        let (size, seed, taps) = (lookup_table.size, lookup_table.seed, lookup_table.taps);
+        let lookup_table = Arc::new(SharedLookupTable::from(lookup_table));
+        Self::with_lookup_table(size, seed, taps, lookup_table)
+    }
+
+    fn with_lookup_table(
+        size: usize,
+        seed: u32,
+        taps: u32,
+        lookup_table: Arc<SharedLookupTable>,
+    ) -> Self {
         Self {
             lookup_table,
             size,
+            taps,
             bitstream: seed,
+            raw_bitstream: seed,
             valid_bits: size,
+            max_bit_errors: 0,
+            error_history: 0,
         }
     }
 
-    /// Process a single bit in forwards direction.
+    /// Returns `true` if the lookup table is ready, i.e. positions can be retrieved.
+    pub fn is_lookup_table_ready(&self) -> bool {
+        self.lookup_table.get().is_some()
+    }
+
+    /// Set the number of bit errors within the last `size` bits that are corrected before the
+    /// bitstream is considered invalid.
     ///
-    /// If the positions before and after inserting the bit are not consecutive, the bitstream
-    /// is marked as invalid. Processing more bits will let the bitstream become valid again.
-    pub fn process_bit(&mut self, bit: u32) {
-        let prev_position = self.position();
-        self.bitstream = bits::insert_msb(self.size, self.bitstream, bit);
-        if let Some(prev_position) = prev_position {
-            let next_position = self.position();
-            if let Some(next_position) = next_position {
-                if prev_position + 1 != next_position {
-                    // Discard all previously processed bits
-                    self.valid_bits = 0
-                }
-            }
+    /// If this is 0 (the default), every bit that doesn't match the expected next bit
+    /// invalidates the bitstream.
+    pub fn set_max_bit_errors(&mut self, max_bit_errors: usize) {
+        self.max_bit_errors = max_bit_errors;
+    }
+
+    /// Returns the number of bit errors within the last `size` bits that were corrected.
+    pub fn recent_bit_errors(&self) -> usize {
+        self.error_history.count_ones() as usize
+    }
+
+    fn lfsr(&self) -> FibonacciLfsr {
+        FibonacciLfsr {
+            size: self.size,
+            state: self.bitstream,
+            taps: self.taps,
         }
-        self.valid_bits += 1;
     }
 
-    /// Process a single bit in backwards direction.
+    /// Process a single bit in forwards direction.
     ///
-    /// If the positions before and after inserting the bit are not consecutive, the bitstream
-    /// is marked as invalid. Processing more bits will let the bitstream become valid again.
-    pub fn process_bit_backward(&mut self, bit: u32) {
-        let prev_position = self.position();
-        self.bitstream = bits::insert_lsb(self.size, self.bitstream, bit);
-        if let Some(prev_position) = prev_position {
-            let next_position = self.position();
-            if let Some(next_position) = next_position {
-                if prev_position != next_position + 1 {
-                    // Discard all previously processed bits
-                    self.valid_bits = 0;
-                }
-            }
+    /// If the bit doesn't match the expected next bit, it's corrected as long as no more than
+    /// `max_bit_errors` were corrected within the last `size` bits. Otherwise, the bitstream is
+    /// marked as invalid. Processing more bits will let the bitstream become valid again. Bits
+    /// with a low confidence that don't match the expected bit are corrected without counting
+    /// them as bit errors.
+    ///
+    /// Returns `false` if the bitstream was valid and the bit didn't match the expected bit.
+    pub fn process_soft_bit(&mut self, bit: SoftBit) -> bool {
+        let value = u32::from(bit.value);
+        self.raw_bitstream = bits::insert_msb(self.size, self.raw_bitstream, value);
+        let expected_state = self.lfsr().next_state();
+        let state = bits::insert_msb(self.size, self.bitstream, value);
+        self.update_state(state, expected_state, bit.confidence)
+    }
+
+    /// Process a single bit in backwards direction (see [`Bitstream::process_soft_bit`]).
+    ///
+    /// Returns `false` if the bitstream was valid and the bit didn't match the expected bit.
+    pub fn process_soft_bit_backward(&mut self, bit: SoftBit) -> bool {
+        let value = u32::from(bit.value);
+        self.raw_bitstream = bits::insert_lsb(self.size, self.raw_bitstream, value);
+        let expected_state = self.lfsr().previous_state();
+        let state = bits::insert_lsb(self.size, self.bitstream, value);
+        self.update_state(state, expected_state, bit.confidence)
+    }
+
+    fn update_state(&mut self, state: u32, expected_state: u32, confidence: f64) -> bool {
+        self.error_history = (self.error_history << 1) & bits::mask(self.size);
+        let matched = !self.is_valid() || state == expected_state;
+        if matched {
+            self.bitstream = state;
+        } else if confidence < Self::MIN_CONFIDENCE {
+            // The reading was too close to the threshold to contradict the expected bit.
+            self.bitstream = expected_state;
+        } else if self.recent_bit_errors() < self.max_bit_errors {
+            // Assume that the bit was misread and continue with the expected state.
+            self.error_history |= 1;
+            self.bitstream = expected_state;
+        } else {
+            // Discard all previously processed bits and continue with the bits that were
+            // actually read.
+            self.bitstream = self.raw_bitstream;
+            self.error_history = 0;
+            self.valid_bits = 0;
         }
         self.valid_bits += 1;
+        matched
+    }
+
+    /// Mark the bitstream as invalid, so that `size` bits need to be read again before a
+    /// position is reported.
+    pub fn invalidate(&mut self) {
+        self.valid_bits = 0;
+        self.error_history = 0;
     }
 
     /// Returns `true` if the position is considered valid.
@@ -97,6 +555,8 @@ impl Bitstream {
 
     pub fn set_state(&mut self, state: u32) {
         self.bitstream = state;
+        self.raw_bitstream = state;
+        self.error_history = 0;
     }
 
     /// Retrieve the Position from the current bitstream.
@@ -107,15 +567,29 @@ impl Bitstream {
             return None;
         }
 
-        self.lookup_table
-            .get(&self.bitstream)
-            .map(ToOwned::to_owned)
+        self.lookup_table.get()?.get(self.bitstream)
+    }
+}
+
+#[cfg(test)]
+impl Bitstream {
+    /// Process a bit that is known for certain in forwards direction.
+    pub fn process_bit(&mut self, bit: u32) {
+        self.process_soft_bit(SoftBit::certain(bit & 1 == 1));
+    }
+
+    /// Process a bit that is known for certain in backwards direction.
+    pub fn process_bit_backward(&mut self, bit: u32) {
+        self.process_soft_bit_backward(SoftBit::certain(bit & 1 == 1));
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Bitstream;
+    use super::{adler32, Bitstream, LookupTable, LookupTableError, SoftBit};
+    use crate::format::FormatError;
+    use crate::{lfsr::FibonacciLfsr, SERATO_CONTROL_CD_1_0_0};
+    use std::{collections::HashMap, hint::black_box, sync::Arc, time::Instant};
 
     #[test]
     fn test_lookup_table() {
@@ -271,4 +745,326 @@ mod test {
 
         assert_ne!(consecutive0, consecutive1);
     }
+
+    #[test]
+    fn test_lookup_table_matches_lfsr() {
+        let mut lfsr = FibonacciLfsr {
+            size: 8,
+            state: 0b11110000,
+            taps: 0b00011101,
+        };
+        let table = LookupTable::new(8, lfsr.state, lfsr.taps);
+        for position in 0..255 {
+            assert_eq!(table.get(lfsr.state), Some(position));
+            lfsr.advance();
+        }
+
+        // The zero state and states beyond the LFSR size are never reached.
+        assert_eq!(table.get(0), None);
+        assert_eq!(table.get(0b1_0000_0000), None);
+        assert_eq!(table.get(u32::MAX), None);
+    }
+
+    #[test]
+    fn test_lookup_table_length() {
+        assert_eq!(LookupTable::length(10), Some(1024));
+
+        // The table for a 31-bit LFSR needs 8 GiB, which can't be addressed on 32-bit targets.
+        let expected = cfg!(target_pointer_width = "64").then_some(1 << 31);
+        assert_eq!(LookupTable::length(31), expected);
+    }
+
+    #[test]
+    fn test_shared_lookup_table() {
+        let first = Bitstream::new(8, 0b00000011, 0b00011101);
+        let second = Bitstream::new(8, 0b00000011, 0b00011101);
+        assert!(Arc::ptr_eq(&first.lookup_table, &second.lookup_table));
+
+        // A different seed results in different positions.
+        let other = Bitstream::new(8, 0b00000111, 0b00011101);
+        assert!(!Arc::ptr_eq(&first.lookup_table, &other.lookup_table));
+
+        // The table is freed when the last bitstream using it is dropped.
+        let table = Arc::downgrade(&first.lookup_table);
+        drop(first);
+        assert!(table.upgrade().is_some());
+        drop(second);
+        assert!(table.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_deferred_lookup_table() {
+        let (size, seed, taps) = (16, 0b1, 0b0001_0000_0000_1011);
+        let mut lfsr = FibonacciLfsr {
+            size,
+            state: seed,
+            taps,
+        };
+        let mut bitstream = Bitstream::new_deferred(size, seed, taps);
+        let mut expected_position = 0;
+        loop {
+            let position = bitstream.position();
+            if bitstream.is_lookup_table_ready() {
+                break;
+            }
+            assert_eq!(position, None);
+
+            // Bits can be processed while the table is being built.
+            bitstream.process_bit(lfsr.advance() >> (size - 1));
+            expected_position += 1;
+            std::thread::yield_now();
+        }
+        assert_eq!(bitstream.position(), Some(expected_position));
+
+        // Another bitstream with the same parameters can use the finished table immediately.
+        let other = Bitstream::new_deferred(size, seed, taps);
+        assert!(other.is_lookup_table_ready());
+        assert_eq!(other.position(), Some(0));
+    }
+
+    #[test]
+    fn test_bit_error_correction() {
+        let mut lfsr = FibonacciLfsr {
+            size: 8,
+            state: 0b00000001,
+            taps: 0b00011101,
+        };
+        let mut bitstream = Bitstream::new(8, lfsr.state, lfsr.taps);
+        bitstream.set_max_bit_errors(1);
+        let mut next_bit = || lfsr.advance() >> 7;
+
+        // A single misread bit is corrected.
+        for position in 1..=20 {
+            let bit = next_bit();
+            let bit = if position == 15 { bit ^ 1 } else { bit };
+            bitstream.process_bit(bit);
+            assert_eq!(bitstream.position(), Some(position));
+        }
+        assert_eq!(bitstream.recent_bit_errors(), 1);
+
+        // The error is forgotten after `size` bits.
+        for position in 21..=30 {
+            bitstream.process_bit(next_bit());
+            assert_eq!(bitstream.position(), Some(position));
+        }
+        assert_eq!(bitstream.recent_bit_errors(), 0);
+
+        // Two errors within `size` bits invalidate the bitstream.
+        bitstream.process_bit(next_bit() ^ 1);
+        assert_eq!(bitstream.position(), Some(31));
+        bitstream.process_bit(next_bit());
+        assert_eq!(bitstream.position(), Some(32));
+        bitstream.process_bit(next_bit() ^ 1);
+        assert_eq!(bitstream.position(), None);
+        assert_eq!(bitstream.recent_bit_errors(), 0);
+
+        // The bitstream resynchronizes using the bits that were actually read.
+        for _ in 0..6 {
+            bitstream.process_bit(next_bit());
+            assert_eq!(bitstream.position(), None);
+        }
+        for position in 40..=60 {
+            bitstream.process_bit(next_bit());
+            if position > 56 {
+                assert_eq!(bitstream.position(), Some(position));
+            }
+        }
+    }
+
+    #[test]
+    fn test_bit_error_correction_backward() {
+        let mut lfsr = FibonacciLfsr {
+            size: 8,
+            state: 0b10110011,
+            taps: 0b00011101,
+        };
+        let mut bitstream = Bitstream::new(8, lfsr.state, lfsr.taps);
+        bitstream.set_max_bit_errors(1);
+
+        for position in (240..255).rev() {
+            let bit = lfsr.revert() & 1;
+            let bit = if position == 250 { bit ^ 1 } else { bit };
+            bitstream.process_bit_backward(bit);
+            assert_eq!(bitstream.position(), Some(position));
+        }
+    }
+
+    #[test]
+    fn test_soft_bits() {
+        let mut lfsr = FibonacciLfsr {
+            size: 8,
+            state: 0b00000001,
+            taps: 0b00011101,
+        };
+        let mut bitstream = Bitstream::new(8, lfsr.state, lfsr.taps);
+        let mut next_bit = || lfsr.advance() >> 7 == 1;
+
+        // Marginal readings that contradict the expected bit don't invalidate the bitstream.
+        for position in 1..=20 {
+            let value = next_bit();
+            let bit = if position % 3 == 0 {
+                SoftBit {
+                    value: !value,
+                    confidence: 0.2,
+                }
+            } else {
+                SoftBit {
+                    value,
+                    confidence: 0.9,
+                }
+            };
+            bitstream.process_soft_bit(bit);
+            assert_eq!(bitstream.position(), Some(position));
+        }
+        assert_eq!(bitstream.recent_bit_errors(), 0);
+
+        // Confident readings do.
+        bitstream.process_soft_bit(SoftBit {
+            value: !next_bit(),
+            confidence: 0.8,
+        });
+        assert_eq!(bitstream.position(), None);
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        assert_eq!(adler32(&[0xff; 10000]), 0xB623_EB2B);
+    }
+
+    #[test]
+    fn test_lookup_table_roundtrip() {
+        let table = LookupTable::new(10, 0b1, 0b00_0000_1001);
+        let mut data = Vec::new();
+        table.write_to(&mut data).unwrap();
+        assert_eq!(data.len(), 24 + 1024 * 4);
+        assert_eq!(LookupTable::read_from(data.as_slice()).unwrap(), table);
+
+        let bitstream = Bitstream::from_lookup_table(table);
+        assert_eq!(bitstream.position(), Some(0));
+
+        // Loaded tables are not shared with other bitstreams.
+        let other = Bitstream::new(10, 0b1, 0b00_0000_1001);
+        assert!(!Arc::ptr_eq(&bitstream.lookup_table, &other.lookup_table));
+    }
+
+    #[test]
+    fn test_lookup_table_read_errors() {
+        let table = LookupTable::new(10, 0b1, 0b00_0000_1001);
+        let mut data = Vec::new();
+        table.write_to(&mut data).unwrap();
+
+        let mut corrupted = data.clone();
+        corrupted[0] = b'X';
+        assert!(matches!(
+            LookupTable::read_from(corrupted.as_slice()),
+            Err(LookupTableError::InvalidMagic)
+        ));
+
+        let mut corrupted = data.clone();
+        corrupted[4] = 2;
+        assert!(matches!(
+            LookupTable::read_from(corrupted.as_slice()),
+            Err(LookupTableError::UnsupportedVersion(2))
+        ));
+
+        let mut corrupted = data.clone();
+        corrupted[8] = 32;
+        assert!(matches!(
+            LookupTable::read_from(corrupted.as_slice()),
+            Err(LookupTableError::InvalidFormat(FormatError::InvalidSize {
+                size: 32
+            }))
+        ));
+
+        let mut corrupted = data.clone();
+        corrupted[100] ^= 1;
+        assert!(matches!(
+            LookupTable::read_from(corrupted.as_slice()),
+            Err(LookupTableError::ChecksumMismatch)
+        ));
+
+        assert!(matches!(
+            LookupTable::read_from(&data[..data.len() - 1]),
+            Err(LookupTableError::Io(_))
+        ));
+
+        // A header for a huge table without any data must not allocate the whole table.
+        let mut header = data[..24].to_vec();
+        header[8] = 31;
+        assert!(matches!(
+            LookupTable::read_from(header.as_slice()),
+            Err(LookupTableError::Io(_))
+        ));
+
+        // A table with a valid checksum, but positions that don't match the LFSR.
+        let mut wrong_table = table.clone();
+        wrong_table.positions.swap(0b1, 0b10_0000_0000);
+        let mut wrong_data = Vec::new();
+        wrong_table.write_to(&mut wrong_data).unwrap();
+        assert!(matches!(
+            LookupTable::read_from(wrong_data.as_slice()),
+            Err(LookupTableError::InvalidPositions)
+        ));
+    }
+
+    /// Compares the lookup table with the `HashMap` that was used previously.
+    ///
+    /// Run with `cargo test --release -- --ignored --nocapture bench_lookup_table`.
+    #[test]
+    #[ignore]
+    fn bench_lookup_table() {
+        let format = &SERATO_CONTROL_CD_1_0_0;
+        let capacity = 2u32.pow(format.size as u32) - 1;
+
+        let start = Instant::now();
+        let mut lfsr = FibonacciLfsr {
+            size: format.size,
+            state: format.seed,
+            taps: format.taps,
+        };
+        let mut hash_map = HashMap::with_capacity(capacity as usize);
+        for i in 0..capacity {
+            hash_map.insert(lfsr.state, i);
+            lfsr.advance();
+        }
+        let hash_map_construction = start.elapsed();
+
+        let start = Instant::now();
+        let table = LookupTable::new(format.size, format.seed, format.taps);
+        let table_construction = start.elapsed();
+
+        let states: Vec<u32> = (0..capacity)
+            .map(|i| i.wrapping_mul(2_654_435_761) & capacity)
+            .collect();
+
+        let start = Instant::now();
+        for state in &states {
+            black_box(hash_map.get(black_box(state)));
+        }
+        let hash_map_lookup = start.elapsed() / capacity;
+
+        let start = Instant::now();
+        for &state in &states {
+            black_box(table.get(black_box(state)));
+        }
+        let table_lookup = start.elapsed() / capacity;
+
+        // Each entry in a `HashMap` needs space for key and value plus one control byte.
+        let hash_map_memory = hash_map.capacity() * (2 * std::mem::size_of::<u32>() + 1);
+
+        println!(
+            "HashMap:      construction {:?}, lookup {:?}, memory {} KiB",
+            hash_map_construction,
+            hash_map_lookup,
+            hash_map_memory / 1024
+        );
+        println!(
+            "Lookup table: construction {:?}, lookup {:?}, memory {} KiB",
+            table_construction,
+            table_lookup,
+            table.memory_usage() / 1024
+        );
+    }
 }
@@ -0,0 +1,502 @@
+// Copyright (c) 2025 Jan Holthuis <holthuis.jan@gmail.com> et al.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! The [`FormatDetector` struct](FormatDetector) detects the timecode format from incoming audio.
+
+use crate::{
+    format::{FormatInfo, FORMATS},
+    pitch::PitchDetector,
+    sample::Sample,
+    timecode::{Timecode, TimecodeChannel},
+};
+
+/// A format that the incoming audio is matched against.
+#[derive(Debug)]
+struct Candidate {
+    info: &'static FormatInfo,
+    timecode: Timecode,
+    previous_position: Option<u32>,
+    consecutive_positions: usize,
+}
+
+impl Candidate {
+    fn new(info: &'static FormatInfo, sample_rate_hz: f64) -> Self {
+        Self {
+            info,
+            timecode: Timecode::new_deferred(info.format, sample_rate_hz),
+            previous_position: None,
+            consecutive_positions: 0,
+        }
+    }
+
+    /// Process a sample and returns `true` if enough consecutive positions were decoded.
+    fn process_channels(&mut self, left_sample: f64, right_sample: f64) -> bool {
+        let status = self.timecode.process_channels(left_sample, right_sample);
+        if status.bit.is_none() {
+            return false;
+        }
+
+        let is_consecutive = match (self.previous_position, status.position) {
+            // Positions beyond the end of the medium can't be produced by this format.
+            (_, Some(current)) if current >= self.timecode.format().length => false,
+            (Some(previous), Some(current)) => {
+                if self.timecode.is_counting_up() {
+                    previous.checked_add(1) == Some(current)
+                } else {
+                    previous.checked_sub(1) == Some(current)
+                }
+            }
+            _ => false,
+        };
+        self.consecutive_positions = if is_consecutive {
+            self.consecutive_positions + 1
+        } else {
+            0
+        };
+        self.previous_position = status.position;
+
+        self.consecutive_positions >= FormatDetector::REQUIRED_CONSECUTIVE_POSITIONS
+    }
+}
+
+/// Detects the timecode format from incoming audio.
+///
+/// Detection happens in two stages:
+///
+/// 1. The carrier frequency of the signal is estimated from the zero crossing rate during the
+///    first few hundred milliseconds.
+/// 2. All formats with a matching carrier frequency are decoded in parallel, and the first format
+///    that produces a sufficiently long run of consecutive positions is reported.
+///
+/// If no format matches the estimated frequency or none of the candidates is detected in time
+/// (e.g. because the estimate was disturbed by hum or a platter that was still speeding up), the
+/// estimation starts over.
+///
+/// Formats that use the same LFSR and carrier frequency (e.g. [`MIXVIBES_V2`] and
+/// [`MIXVIBES_7INCH`]) produce identical audio, so they can't be told apart. In that case, all
+/// matching formats are reported (in the order in which they were passed to the detector), and
+/// the caller needs to pick one (e.g. by asking the user). Positions beyond the end of a shorter
+/// medium rule it out, though.
+///
+/// Note that the second stage creates a [`Timecode`] for each candidate format. Their lookup
+/// tables are built on background threads (see [`Timecode::new_deferred`]), so that processing
+/// samples doesn't block, but the candidates can't be detected until the tables are ready.
+///
+/// [`MIXVIBES_V2`]: crate::MIXVIBES_V2
+/// [`MIXVIBES_7INCH`]: crate::MIXVIBES_7INCH
+#[derive(Debug)]
+pub struct FormatDetector {
+    formats: Vec<&'static FormatInfo>,
+    sample_rate_hz: f64,
+    left_channel: TimecodeChannel,
+    right_channel: TimecodeChannel,
+    frequency_detector: PitchDetector,
+    estimation_samples: usize,
+    samples_processed: usize,
+    frequency_sum_hz: f64,
+    zero_crossings: usize,
+    estimated_frequency_hz: Option<f64>,
+    candidates: Vec<Candidate>,
+    detection_samples: usize,
+    timeout_samples: usize,
+    detected_formats: Vec<&'static FormatInfo>,
+}
+
+impl FormatDetector {
+    /// Duration of the carrier frequency estimation stage.
+    const ESTIMATION_SECS: f64 = 0.25;
+
+    /// Zero crossings at the start of the estimation stage that are ignored, because the zero
+    /// crossing detection needs to settle.
+    const IGNORED_ZERO_CROSSINGS: usize = 16;
+
+    /// Maximum relative deviation of the estimated carrier frequency from the format's signal
+    /// frequency. This needs to cover the pitch range of common turntables (±8 %) while
+    /// still distinguishing between 1000 Hz and 1300 Hz carriers.
+    const FREQUENCY_TOLERANCE: f64 = 0.12;
+
+    /// Number of consecutive positions that need to be decoded before a format is reported.
+    const REQUIRED_CONSECUTIVE_POSITIONS: usize = 50;
+
+    /// Time after which the estimation starts over if none of the candidates was detected. Time
+    /// spent waiting for the lookup tables doesn't count.
+    const DETECTION_TIMEOUT_SECS: f64 = 2.0;
+
+    /// Create a detector that considers all built-in formats.
+    pub fn new(sample_rate_hz: f64) -> Self {
+        Self::with_formats(FORMATS.iter(), sample_rate_hz)
+    }
+
+    /// Create a detector that only considers the given formats.
+    pub fn with_formats(
+        formats: impl IntoIterator<Item = &'static FormatInfo>,
+        sample_rate_hz: f64,
+    ) -> Self {
+        Self {
+            formats: formats.into_iter().collect(),
+            sample_rate_hz,
+            left_channel: TimecodeChannel::new(sample_rate_hz),
+            right_channel: TimecodeChannel::new(sample_rate_hz),
+            // With a nominal frequency of 1 Hz, the detected pitch is the frequency in Hz.
+            frequency_detector: PitchDetector::new(sample_rate_hz, 1.0),
+            estimation_samples: (Self::ESTIMATION_SECS * sample_rate_hz) as usize,
+            samples_processed: 0,
+            frequency_sum_hz: 0.0,
+            zero_crossings: 0,
+            estimated_frequency_hz: None,
+            candidates: Vec::new(),
+            detection_samples: 0,
+            timeout_samples: (Self::DETECTION_TIMEOUT_SECS * sample_rate_hz) as usize,
+            detected_formats: Vec::new(),
+        }
+    }
+
+    /// Forget the detected formats and start over, e.g. when the input was switched to another
+    /// turntable.
+    pub fn reset(&mut self) {
+        let formats = std::mem::take(&mut self.formats);
+        *self = Self::with_formats(formats, self.sample_rate_hz);
+    }
+
+    /// Returns the estimated carrier frequency (if the estimation stage is finished).
+    pub fn estimated_frequency_hz(&self) -> Option<f64> {
+        self.estimated_frequency_hz
+    }
+
+    /// Returns the detected formats (or an empty slice if detection didn't succeed yet).
+    ///
+    /// This contains more than one format if the matching formats can't be told apart.
+    pub fn detected_formats(&self) -> &[&'static FormatInfo] {
+        &self.detected_formats
+    }
+
+    /// Returns the formats that are still considered after the estimation stage.
+    pub fn candidates(&self) -> impl Iterator<Item = &'static FormatInfo> + '_ {
+        self.candidates.iter().map(|candidate| candidate.info)
+    }
+
+    /// Process a single sample for the left and right channel.
+    ///
+    /// Returns the detected formats as soon as the detection succeeded (see
+    /// [`FormatDetector::detected_formats`]).
+    pub fn process_channels<S: Sample>(
+        &mut self,
+        left_sample: S,
+        right_sample: S,
+    ) -> Option<&[&'static FormatInfo]> {
+        if !self.detected_formats.is_empty() {
+            return Some(&self.detected_formats);
+        }
+
+        let left_sample = left_sample.to_f64();
+        let right_sample = right_sample.to_f64();
+        if self.estimated_frequency_hz.is_none() {
+            self.estimate_frequency(left_sample, right_sample);
+            return None;
+        }
+
+        // All candidates need to process the sample, because formats with the same LFSR match
+        // at the same time.
+        let mut lookup_tables_ready = true;
+        for candidate in &mut self.candidates {
+            lookup_tables_ready &= candidate.timecode.is_lookup_table_ready();
+            if candidate.process_channels(left_sample, right_sample) {
+                self.detected_formats.push(candidate.info);
+            }
+        }
+        if !self.detected_formats.is_empty() {
+            return Some(&self.detected_formats);
+        }
+
+        if lookup_tables_ready {
+            self.detection_samples += 1;
+            if self.detection_samples >= self.timeout_samples {
+                self.restart_estimation();
+            }
+        }
+        None
+    }
+
+    /// Discard the estimated frequency and the candidates and start a new estimation.
+    fn restart_estimation(&mut self) {
+        self.samples_processed = 0;
+        self.zero_crossings = 0;
+        self.frequency_sum_hz = 0.0;
+        self.estimated_frequency_hz = None;
+        self.candidates.clear();
+        self.detection_samples = 0;
+    }
+
+    fn estimate_frequency(&mut self, left_sample: f64, right_sample: f64) {
+        let left_crossed_zero = self.left_channel.process_sample(left_sample);
+        let right_crossed_zero = self.right_channel.process_sample(right_sample);
+        if left_crossed_zero || right_crossed_zero {
+            let frequency_hz = self.frequency_detector.update_after_zero_crossing(
+                left_sample,
+                right_sample,
+                left_crossed_zero,
+            );
+            self.zero_crossings += 1;
+            if self.zero_crossings > Self::IGNORED_ZERO_CROSSINGS {
+                self.frequency_sum_hz += frequency_hz;
+            }
+        } else {
+            self.frequency_detector.update(left_sample, right_sample);
+        }
+
+        self.samples_processed += 1;
+        if self.samples_processed < self.estimation_samples {
+            return;
+        }
+
+        let Some(measured_zero_crossings) = self
+            .zero_crossings
+            .checked_sub(Self::IGNORED_ZERO_CROSSINGS)
+            .filter(|&count| count > 0)
+        else {
+            // No signal, restart the estimation.
+            self.restart_estimation();
+            return;
+        };
+
+        let estimated_frequency_hz = self.frequency_sum_hz / measured_zero_crossings as f64;
+        let candidates: Vec<Candidate> = self
+            .formats
+            .iter()
+            .filter(|info| Self::matches_frequency(info, estimated_frequency_hz))
+            .map(|info| Candidate::new(info, self.sample_rate_hz))
+            .collect();
+        if candidates.is_empty() {
+            // The signal is not a timecode (yet), e.g. hum or rumble.
+            self.restart_estimation();
+            return;
+        }
+
+        self.estimated_frequency_hz = Some(estimated_frequency_hz);
+        self.candidates = candidates;
+    }
+
+    /// Returns `true` if the carrier frequency of the format matches the estimated frequency.
+    fn matches_frequency(info: &FormatInfo, estimated_frequency_hz: f64) -> bool {
+        let deviation = estimated_frequency_hz / info.format.signal_frequency_hz - 1.0;
+        deviation.abs() <= Self::FREQUENCY_TOLERANCE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FormatDetector;
+    use crate::{
+        find_format, TimecodeAudioGenerator, TimecodeFormat, MIXVIBES_7INCH, MIXVIBES_V2,
+        PIONEER_REKORDBOX_SIDE_B, SERATO_CONTROL_CD_1_0_0, TRAKTOR_SCRATCH_MK1_SIDE_A,
+    };
+    use std::{thread, time::Duration};
+
+    const SAMPLE_RATE_HZ: f64 = 44100.0;
+
+    /// Block until the lookup tables of all candidates are built, so that the tests don't depend
+    /// on how fast the background threads are.
+    fn wait_for_lookup_tables(detector: &FormatDetector) {
+        while !detector
+            .candidates
+            .iter()
+            .all(|candidate| candidate.timecode.is_lookup_table_ready())
+        {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Feed a sine wave with the given frequency into the detector, using a phase difference of
+    /// 90° between both channels.
+    fn feed_sine(detector: &mut FormatDetector, frequency_hz: f64, amplitude: f64, secs: f64) {
+        for index in 0..(secs * SAMPLE_RATE_HZ) as usize {
+            wait_for_lookup_tables(detector);
+            let angle = 2.0 * std::f64::consts::PI * frequency_hz * index as f64 / SAMPLE_RATE_HZ;
+            let (left, right) = angle.sin_cos();
+            assert_eq!(
+                detector.process_channels(left * amplitude, right * amplitude),
+                None
+            );
+        }
+    }
+
+    /// Feed up to 5 seconds of generated audio into the detector.
+    fn detect(
+        detector: &mut FormatDetector,
+        format: &TimecodeFormat,
+        generator_sample_rate_hz: f64,
+    ) -> Option<Vec<&'static str>> {
+        let mut generator = TimecodeAudioGenerator::new(format, generator_sample_rate_hz);
+        for _ in 0..(5.0 * SAMPLE_RATE_HZ) as usize {
+            wait_for_lookup_tables(detector);
+            let (left, right) = generator.next_sample();
+            if let Some(formats) = detector.process_channels(left, right) {
+                return Some(formats.iter().map(|info| info.name).collect());
+            }
+        }
+        None
+    }
+
+    fn detect_among(names: &[&str], format: &TimecodeFormat) -> Option<Vec<&'static str>> {
+        let formats = names.iter().map(|name| find_format(name).unwrap());
+        let mut detector = FormatDetector::with_formats(formats, SAMPLE_RATE_HZ);
+        detect(&mut detector, format, SAMPLE_RATE_HZ)
+    }
+
+    #[test]
+    fn test_detect_serato_cd() {
+        let mut detector = FormatDetector::new(SAMPLE_RATE_HZ);
+        assert_eq!(
+            detect(&mut detector, &SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ),
+            Some(vec!["serato_cd"])
+        );
+
+        let frequency_hz = detector.estimated_frequency_hz().unwrap();
+        assert!((frequency_hz - 1000.0).abs() < 10.0, "{}", frequency_hz);
+
+        // Only formats with a 1000 Hz carrier are considered.
+        let mut candidates: Vec<&str> = detector.candidates().map(|info| info.name).collect();
+        candidates.sort_unstable();
+        assert_eq!(
+            candidates,
+            [
+                "pioneer_a",
+                "pioneer_b",
+                "serato_2a",
+                "serato_2b",
+                "serato_cd"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_traktor() {
+        assert_eq!(
+            detect_among(
+                &["serato_cd", "traktor_a", "mixvibes_v2"],
+                &TRAKTOR_SCRATCH_MK1_SIDE_A
+            ),
+            Some(vec!["traktor_a"])
+        );
+    }
+
+    #[test]
+    fn test_detect_mixvibes() {
+        assert_eq!(
+            detect_among(&["serato_cd", "traktor_a", "mixvibes_v2"], &MIXVIBES_V2),
+            Some(vec!["mixvibes_v2"])
+        );
+    }
+
+    #[test]
+    fn test_detect_mixvibes_7inch() {
+        // Both formats use the same timecode, so they can't be told apart.
+        let mut detector = FormatDetector::new(SAMPLE_RATE_HZ);
+        assert_eq!(
+            detect(&mut detector, &MIXVIBES_7INCH, SAMPLE_RATE_HZ),
+            Some(vec!["mixvibes_v2", "mixvibes_7inch"])
+        );
+        let names: Vec<&str> = detector
+            .detected_formats()
+            .iter()
+            .map(|info| info.name)
+            .collect();
+        assert_eq!(names, ["mixvibes_v2", "mixvibes_7inch"]);
+    }
+
+    #[test]
+    fn test_detect_pioneer_side_b() {
+        assert_eq!(
+            detect_among(
+                &["serato_cd", "pioneer_a", "pioneer_b"],
+                &PIONEER_REKORDBOX_SIDE_B
+            ),
+            Some(vec!["pioneer_b"])
+        );
+    }
+
+    #[test]
+    fn test_detect_with_pitch() {
+        // Generating at a lower sample rate than the detector uses speeds up the playback.
+        let mut detector = FormatDetector::new(SAMPLE_RATE_HZ);
+        assert_eq!(
+            detect(
+                &mut detector,
+                &SERATO_CONTROL_CD_1_0_0,
+                SAMPLE_RATE_HZ / 1.08
+            ),
+            Some(vec!["serato_cd"])
+        );
+
+        let frequency_hz = detector.estimated_frequency_hz().unwrap();
+        assert!((frequency_hz - 1080.0).abs() < 10.0, "{}", frequency_hz);
+    }
+
+    #[test]
+    fn test_detect_after_hum() {
+        let mut detector = FormatDetector::new(SAMPLE_RATE_HZ);
+        feed_sine(&mut detector, 50.0, 0.1, 0.5);
+        assert_eq!(detector.estimated_frequency_hz(), None);
+        assert_eq!(detector.candidates().count(), 0);
+
+        assert_eq!(
+            detect(&mut detector, &SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ),
+            Some(vec!["serato_cd"])
+        );
+    }
+
+    #[test]
+    fn test_detect_after_timeout() {
+        // A carrier without any data matches the frequency of some formats, but none of them
+        // produces consecutive positions.
+        let mut detector = FormatDetector::new(SAMPLE_RATE_HZ);
+        let mut estimated = false;
+        let mut restarted = false;
+        for _ in 0..30 {
+            feed_sine(&mut detector, 1000.0, 0.5, 0.1);
+            match detector.estimated_frequency_hz() {
+                Some(_) => estimated = true,
+                None => restarted |= estimated,
+            }
+        }
+        assert!(restarted);
+
+        assert_eq!(
+            detect(&mut detector, &SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ),
+            Some(vec!["serato_cd"])
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut detector = FormatDetector::new(SAMPLE_RATE_HZ);
+        assert_eq!(
+            detect(&mut detector, &SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ),
+            Some(vec!["serato_cd"])
+        );
+
+        detector.reset();
+        assert!(detector.detected_formats().is_empty());
+        assert_eq!(detector.estimated_frequency_hz(), None);
+        assert_eq!(detector.candidates().count(), 0);
+        assert_eq!(
+            detect(&mut detector, &PIONEER_REKORDBOX_SIDE_B, SAMPLE_RATE_HZ),
+            Some(vec!["pioneer_b"])
+        );
+    }
+
+    #[test]
+    fn test_detect_silence() {
+        let mut detector = FormatDetector::new(SAMPLE_RATE_HZ);
+        for _ in 0..SAMPLE_RATE_HZ as usize {
+            assert_eq!(detector.process_channels(0i16, 0i16), None);
+        }
+        assert_eq!(detector.estimated_frequency_hz(), None);
+        assert_eq!(detector.candidates().count(), 0);
+    }
+}
@@ -6,12 +6,158 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
+use crate::{bits, lfsr::FibonacciLfsr};
+use std::{error::Error, fmt, ops::Range, str::FromStr, time::Duration};
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TimecodeFormat {
     pub size: usize,
     pub seed: u32,
     pub taps: u32,
     pub signal_frequency_hz: f64,
+    /// Use the right channel as primary channel (instead of the left one).
+    pub switch_primary: bool,
+    /// Read the bits from the negative (instead of the positive) half-wave of the primary
+    /// channel.
+    pub switch_polarity: bool,
+    /// Invert the phase relation between primary and secondary channel that is used to detect
+    /// the playback direction.
+    pub switch_phase: bool,
+    /// Number of valid positions on the physical medium, starting at position 0.
+    pub length: u32,
+    /// First position after the lead-in, i.e. the start of the usable range.
+    ///
+    /// This is 0 if the length of the lead-in is unknown, so that all positions up to the
+    /// lead-out are considered usable. None of the built-in formats specify it yet.
+    pub lead_in: u32,
+    /// First position of the lead-out, i.e. the end of the usable range.
+    pub lead_out: u32,
+    /// Nominal speed of the record in revolutions per minute (or `None` if the timecode is not
+    /// distributed on vinyl).
+    pub rpm: Option<f64>,
+    /// Whether the positions count up or down during forward playback.
+    pub counting: Counting,
+}
+
+/// Nominal speed of a 12" record in revolutions per minute.
+pub const RPM_33: f64 = 100.0 / 3.0;
+
+/// Nominal speed of a 7" record in revolutions per minute.
+pub const RPM_45: f64 = 45.0;
+
+/// The direction in which the positions of a timecode change during forward playback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Counting {
+    Up,
+    Down,
+}
+
+impl TimecodeFormat {
+    /// Convert a position into the time offset from the start of the timecode.
+    ///
+    /// Each position corresponds to a single cycle of the timecode signal, so at a signal
+    /// frequency of 1000 Hz, position 1500 is located 1.5 seconds into the timecode.
+    pub fn position_to_duration(&self, position: u32) -> Duration {
+        Duration::from_secs_f64(f64::from(position) / self.signal_frequency_hz)
+    }
+
+    /// Convert a time offset from the start of the timecode into the closest position.
+    ///
+    /// This is the inverse of [`TimecodeFormat::position_to_duration`]. Offsets beyond the last
+    /// representable position are saturated to `u32::MAX`.
+    pub fn duration_to_position(&self, duration: Duration) -> u32 {
+        (duration.as_secs_f64() * self.signal_frequency_hz).round() as u32
+    }
+
+    /// Smallest supported LFSR size in bits.
+    pub const MIN_SIZE: usize = 2;
+
+    /// Largest supported LFSR size in bits.
+    pub const MAX_SIZE: usize = 31;
+
+    /// Check if the format describes a usable timecode.
+    ///
+    /// Decoders and generators panic on formats that don't pass this check, so formats from
+    /// untrusted sources (e.g. config files) should be validated first.
+    ///
+    /// Note that checking for a maximal-length LFSR requires clocking the LFSR through its whole
+    /// period, which takes a while for large sizes.
+    pub fn validate(&self) -> Result<(), FormatError> {
+        self.validate_parameters()?;
+
+        let lfsr = FibonacciLfsr {
+            size: self.size,
+            state: self.seed,
+            taps: self.taps,
+        };
+        if !lfsr.has_maximal_length() {
+            return Err(FormatError::NotMaximalLength { taps: self.taps });
+        }
+
+        Ok(())
+    }
+
+    /// Like [`TimecodeFormat::validate`], but without the expensive check for a maximal-length
+    /// LFSR.
+    pub(crate) fn validate_parameters(&self) -> Result<(), FormatError> {
+        let size = self.size;
+        if !(Self::MIN_SIZE..=Self::MAX_SIZE).contains(&size) {
+            return Err(FormatError::InvalidSize { size });
+        }
+
+        let mask = bits::mask(size);
+        if self.seed & !mask != 0 {
+            return Err(FormatError::SeedTooLarge {
+                seed: self.seed,
+                size,
+            });
+        }
+        if self.taps & !mask != 0 {
+            return Err(FormatError::TapsTooLarge {
+                taps: self.taps,
+                size,
+            });
+        }
+        if self.seed == 0 {
+            return Err(FormatError::ZeroSeed);
+        }
+        if !self.signal_frequency_hz.is_finite() || self.signal_frequency_hz <= 0.0 {
+            return Err(FormatError::InvalidSignalFrequency {
+                signal_frequency_hz: self.signal_frequency_hz,
+            });
+        }
+        if self.lead_in > self.lead_out || self.lead_out > self.length {
+            return Err(FormatError::InvalidUsableRange {
+                lead_in: self.lead_in,
+                lead_out: self.lead_out,
+                length: self.length,
+            });
+        }
+        // The LFSR repeats after 2^size - 1 positions.
+        if self.length > mask {
+            return Err(FormatError::LengthTooLarge {
+                length: self.length,
+                size,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the range of positions between lead-in and lead-out.
+    pub fn usable_positions(&self) -> Range<u32> {
+        self.lead_in..self.lead_out
+    }
+
+    /// Returns `true` if the position is located between lead-in and lead-out.
+    pub fn is_usable(&self, position: u32) -> bool {
+        self.usable_positions().contains(&position)
+    }
+
+    /// Returns the total playing time of the physical medium at nominal speed.
+    pub fn duration(&self) -> Duration {
+        self.position_to_duration(self.length)
+    }
 }
 
 /// Serato Control CD 1.0.0
@@ -25,19 +171,443 @@ pub const SERATO_CONTROL_CD_1_0_0: TimecodeFormat = TimecodeFormat {
     // x^20 + x^18 + x^16 + x^14 + x^12 + x^10 + x^9 + x^6 + x^4 + x^3 + 1
     taps: 0b0011_0100_1101_0101_0101,
     signal_frequency_hz: 1000.0,
+    switch_primary: false,
+    switch_polarity: false,
+    switch_phase: false,
+    length: 950_000,
+    lead_in: 0,
+    lead_out: 940_000,
+    rpm: None,
+    counting: Counting::Up,
+};
+
+/// Serato Control Vinyl (2nd Edition, "CV02"), Side A
+///
+/// This is the same format that [xwax](https://xwax.org) calls `serato_2a`.
+pub const SERATO_CV02_SIDE_A: TimecodeFormat = TimecodeFormat {
+    size: 20,
+    seed: 0b0101_1001_0000_0001_0111,
+    // LFSR feedback polynomial:
+    // x^20 + x^18 + x^15 + x^14 + x^13 + x^12 + x^7 + x^6 + x^4 + x^3 + 1
+    taps: 0b0011_0110_0001_1110_0101,
+    signal_frequency_hz: 1000.0,
+    switch_primary: false,
+    switch_polarity: false,
+    switch_phase: false,
+    length: 712_000,
+    lead_in: 0,
+    lead_out: 707_000,
+    rpm: Some(RPM_33),
+    counting: Counting::Up,
+};
+
+/// Serato Control Vinyl (2nd Edition, "CV02"), Side B
+///
+/// This is the same format that [xwax](https://xwax.org) calls `serato_2b`.
+pub const SERATO_CV02_SIDE_B: TimecodeFormat = TimecodeFormat {
+    size: 20,
+    seed: 0b1000_1111_0011_1100_0110,
+    // LFSR feedback polynomial:
+    // x^20 + x^17 + x^16 + x^14 + x^13 + x^8 + x^7 + x^6 + x^5 + x^2 + 1
+    taps: 0b0100_1111_0000_1101_1001,
+    signal_frequency_hz: 1000.0,
+    switch_primary: false,
+    switch_polarity: false,
+    switch_phase: false,
+    length: 922_000,
+    lead_in: 0,
+    lead_out: 917_000,
+    rpm: Some(RPM_33),
+    counting: Counting::Up,
+};
+
+/// Traktor Scratch Control Vinyl MK1, Side A
+///
+/// This is the same format that [xwax](https://xwax.org) calls `traktor_a`.
+pub const TRAKTOR_SCRATCH_MK1_SIDE_A: TimecodeFormat = TimecodeFormat {
+    size: 23,
+    seed: 0b001_0011_0100_0101_0000_0011,
+    // LFSR feedback polynomial:
+    // x^23 + x^17 + x^11 + x^5 + 1
+    taps: 0b000_0100_0001_0000_0100_0001,
+    signal_frequency_hz: 2000.0,
+    switch_primary: true,
+    switch_polarity: true,
+    switch_phase: true,
+    length: 1_500_000,
+    lead_in: 0,
+    lead_out: 605_000,
+    rpm: Some(RPM_33),
+    counting: Counting::Up,
 };
 
+/// Traktor Scratch Control Vinyl MK1, Side B
+///
+/// This is the same format that [xwax](https://xwax.org) calls `traktor_b`. It uses the same
+/// LFSR as side A, but starts at a different position.
+pub const TRAKTOR_SCRATCH_MK1_SIDE_B: TimecodeFormat = TimecodeFormat {
+    size: 23,
+    seed: 0b011_0010_0000_0110_0110_1100,
+    // LFSR feedback polynomial:
+    // x^23 + x^17 + x^11 + x^5 + 1
+    taps: 0b000_0100_0001_0000_0100_0001,
+    signal_frequency_hz: 2000.0,
+    switch_primary: true,
+    switch_polarity: true,
+    switch_phase: true,
+    length: 2_110_000,
+    lead_in: 0,
+    lead_out: 907_000,
+    rpm: Some(RPM_33),
+    counting: Counting::Up,
+};
+
+/// MixVibes Control Vinyl V2
+///
+/// This is the same format that [xwax](https://xwax.org) calls `mixvibes_v2`.
+pub const MIXVIBES_V2: TimecodeFormat = TimecodeFormat {
+    size: 20,
+    seed: 0b0010_0010_1100_1001_0000,
+    // LFSR feedback polynomial:
+    // x^20 + x^17 + 1
+    taps: 0b0000_0000_0000_0000_1001,
+    signal_frequency_hz: 1300.0,
+    switch_primary: false,
+    switch_polarity: false,
+    switch_phase: true,
+    length: 950_000,
+    lead_in: 0,
+    lead_out: 923_000,
+    rpm: Some(RPM_33),
+    counting: Counting::Up,
+};
+
+/// MixVibes 7" Control Vinyl
+///
+/// This is the same format that [xwax](https://xwax.org) calls `mixvibes_7inch`. It uses the
+/// same timecode as [`MIXVIBES_V2`], but the record is shorter and plays at 45 RPM.
+pub const MIXVIBES_7INCH: TimecodeFormat = TimecodeFormat {
+    size: 20,
+    seed: 0b0010_0010_1100_1001_0000,
+    // LFSR feedback polynomial:
+    // x^20 + x^17 + 1
+    taps: 0b0000_0000_0000_0000_1001,
+    signal_frequency_hz: 1300.0,
+    switch_primary: false,
+    switch_polarity: false,
+    switch_phase: true,
+    length: 312_000,
+    lead_in: 0,
+    lead_out: 305_000,
+    rpm: Some(RPM_45),
+    counting: Counting::Up,
+};
+
+/// Pioneer Rekordbox DVS Control Vinyl, Side A
+///
+/// This is the same format that [xwax](https://xwax.org) calls `pioneer_a`. Compared to the
+/// Serato formats, the bits are encoded in the negative half-wave of the primary channel.
+pub const PIONEER_REKORDBOX_SIDE_A: TimecodeFormat = TimecodeFormat {
+    size: 20,
+    seed: 0b0111_1000_0011_0111_0000,
+    // LFSR feedback polynomial:
+    // x^20 + x^19 + x^17 + x^16 + x^15 + x^12 + x^11 + x^8 + x^5 + x^4 + x^3 + x^2 + 1
+    taps: 0b0111_1001_0011_0011_1011,
+    signal_frequency_hz: 1000.0,
+    switch_primary: false,
+    switch_polarity: true,
+    switch_phase: false,
+    length: 635_000,
+    lead_in: 0,
+    lead_out: 614_000,
+    rpm: Some(RPM_33),
+    counting: Counting::Up,
+};
+
+/// Pioneer Rekordbox DVS Control Vinyl, Side B
+///
+/// This is the same format that [xwax](https://xwax.org) calls `pioneer_b`. Compared to the
+/// Serato formats, the bits are encoded in the negative half-wave of the primary channel.
+pub const PIONEER_REKORDBOX_SIDE_B: TimecodeFormat = TimecodeFormat {
+    size: 20,
+    seed: 0b1111_0111_0000_0001_0010,
+    // LFSR feedback polynomial:
+    // x^20 + x^18 + x^17 + x^16 + x^12 + x^11 + x^10 + x^9 + x^7 + x^6 + x^5 + x^3 + 1
+    taps: 0b0010_1110_1111_0001_1101,
+    signal_frequency_hz: 1000.0,
+    switch_primary: false,
+    switch_polarity: true,
+    switch_phase: false,
+    length: 918_500,
+    lead_in: 0,
+    lead_out: 913_000,
+    rpm: Some(RPM_33),
+    counting: Counting::Up,
+};
+
+/// The manufacturer of a timecode medium.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vendor {
+    Serato,
+    NativeInstruments,
+    MixVibes,
+    Pioneer,
+}
+
+/// The physical medium that a timecode is distributed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Medium {
+    Vinyl,
+    Cd,
+}
+
+/// The side of a timecode record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    A,
+    B,
+}
+
+/// Describes a built-in timecode format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatInfo {
+    /// The stable name of the format that can be used for lookups (e.g. in config files).
+    ///
+    /// Names are the same that [xwax](https://xwax.org) uses.
+    pub name: &'static str,
+    pub vendor: Vendor,
+    pub medium: Medium,
+    /// The side of the record (if the format is specific to one side).
+    pub side: Option<Side>,
+    pub format: &'static TimecodeFormat,
+}
+
+/// All built-in timecode formats.
+pub static FORMATS: &[FormatInfo] = &[
+    FormatInfo {
+        name: "serato_2a",
+        vendor: Vendor::Serato,
+        medium: Medium::Vinyl,
+        side: Some(Side::A),
+        format: &SERATO_CV02_SIDE_A,
+    },
+    FormatInfo {
+        name: "serato_2b",
+        vendor: Vendor::Serato,
+        medium: Medium::Vinyl,
+        side: Some(Side::B),
+        format: &SERATO_CV02_SIDE_B,
+    },
+    FormatInfo {
+        name: "serato_cd",
+        vendor: Vendor::Serato,
+        medium: Medium::Cd,
+        side: None,
+        format: &SERATO_CONTROL_CD_1_0_0,
+    },
+    FormatInfo {
+        name: "traktor_a",
+        vendor: Vendor::NativeInstruments,
+        medium: Medium::Vinyl,
+        side: Some(Side::A),
+        format: &TRAKTOR_SCRATCH_MK1_SIDE_A,
+    },
+    FormatInfo {
+        name: "traktor_b",
+        vendor: Vendor::NativeInstruments,
+        medium: Medium::Vinyl,
+        side: Some(Side::B),
+        format: &TRAKTOR_SCRATCH_MK1_SIDE_B,
+    },
+    FormatInfo {
+        name: "mixvibes_v2",
+        vendor: Vendor::MixVibes,
+        medium: Medium::Vinyl,
+        side: None,
+        format: &MIXVIBES_V2,
+    },
+    FormatInfo {
+        name: "mixvibes_7inch",
+        vendor: Vendor::MixVibes,
+        medium: Medium::Vinyl,
+        side: None,
+        format: &MIXVIBES_7INCH,
+    },
+    FormatInfo {
+        name: "pioneer_a",
+        vendor: Vendor::Pioneer,
+        medium: Medium::Vinyl,
+        side: Some(Side::A),
+        format: &PIONEER_REKORDBOX_SIDE_A,
+    },
+    FormatInfo {
+        name: "pioneer_b",
+        vendor: Vendor::Pioneer,
+        medium: Medium::Vinyl,
+        side: Some(Side::B),
+        format: &PIONEER_REKORDBOX_SIDE_B,
+    },
+];
+
+/// Look up a built-in timecode format by its name (case-insensitive).
+pub fn find_format(name: &str) -> Option<&'static FormatInfo> {
+    FORMATS
+        .iter()
+        .find(|info| info.name.eq_ignore_ascii_case(name))
+}
+
+/// Error returned when parsing the name of an unknown timecode format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFormatError {
+    pub name: String,
+}
+
+impl fmt::Display for UnknownFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown timecode format: {:?}", self.name)
+    }
+}
+
+impl Error for UnknownFormatError {}
+
+/// Error returned by [`TimecodeFormat::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    /// The LFSR size is not between [`TimecodeFormat::MIN_SIZE`] and
+    /// [`TimecodeFormat::MAX_SIZE`].
+    InvalidSize { size: usize },
+    /// The seed has bits set beyond the LFSR size.
+    SeedTooLarge { seed: u32, size: usize },
+    /// The taps have bits set beyond the LFSR size.
+    TapsTooLarge { taps: u32, size: usize },
+    /// The seed is zero, so the LFSR would never leave that state.
+    ZeroSeed,
+    /// The signal frequency is not a positive, finite number.
+    InvalidSignalFrequency { signal_frequency_hz: f64 },
+    /// The lead-in is after the lead-out or the lead-out is after the end of the medium.
+    InvalidUsableRange {
+        lead_in: u32,
+        lead_out: u32,
+        length: u32,
+    },
+    /// The medium has more positions than the LFSR produces before it repeats.
+    LengthTooLarge { length: u32, size: usize },
+    /// The taps don't produce a maximal-length sequence, so some positions would be ambiguous
+    /// or unreachable.
+    NotMaximalLength { taps: u32 },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSize { size } => write!(
+                f,
+                "LFSR size {} is not between {} and {} bits",
+                size,
+                TimecodeFormat::MIN_SIZE,
+                TimecodeFormat::MAX_SIZE
+            ),
+            Self::SeedTooLarge { seed, size } => {
+                write!(f, "seed {:#x} does not fit into {} bits", seed, size)
+            }
+            Self::TapsTooLarge { taps, size } => {
+                write!(f, "taps {:#x} do not fit into {} bits", taps, size)
+            }
+            Self::ZeroSeed => write!(f, "seed must not be zero"),
+            Self::InvalidSignalFrequency {
+                signal_frequency_hz,
+            } => write!(
+                f,
+                "signal frequency {} Hz is not a positive number",
+                signal_frequency_hz
+            ),
+            Self::InvalidUsableRange {
+                lead_in,
+                lead_out,
+                length,
+            } => write!(
+                f,
+                "lead-in {}, lead-out {} and length {} are not in ascending order",
+                lead_in, lead_out, length
+            ),
+            Self::LengthTooLarge { length, size } => write!(
+                f,
+                "length {} exceeds the period of a {} bit LFSR",
+                length, size
+            ),
+            Self::NotMaximalLength { taps } => {
+                write!(f, "taps {:#x} do not produce a maximal-length LFSR", taps)
+            }
+        }
+    }
+}
+
+impl Error for FormatError {}
+
+impl FromStr for TimecodeFormat {
+    type Err = UnknownFormatError;
+
+    /// Parse the name of a built-in timecode format (see [`find_format`]).
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        find_format(name)
+            .map(|info| info.format.clone())
+            .ok_or_else(|| UnknownFormatError {
+                name: name.to_owned(),
+            })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::{Timecode, TimecodeAudioGenerator};
 
     fn test_format(format: &TimecodeFormat, sample_rate_hz: f64) {
+        // Skip the first few samples until the bit detection works properly
+        check_format(format, sample_rate_hz, 20, usize::MAX);
+    }
+
+    /// Like `test_format`, but skips the first cycles of the signal.
+    ///
+    /// The generated signal is faded in during the first cycle, so the first bits may be misread
+    /// until the bit threshold has adapted to a "1" bit at full amplitude. Unlike for the Serato
+    /// CD, this may take several cycles depending on the seed (e.g. for Serato CV02 side B) and
+    /// the half-wave that the bits are read from, so the first `size` cycles are skipped.
+    fn test_format_settled(format: &TimecodeFormat, sample_rate_hz: f64) {
+        test_format_settled_bits(format, sample_rate_hz, usize::MAX);
+    }
+
+    /// Like `test_format_settled`, but stops after `num_bits` instead of the whole LFSR period.
+    fn test_format_settled_bits(format: &TimecodeFormat, sample_rate_hz: f64, num_bits: usize) {
+        // The warmup needs to end in the middle of a cycle, i.e. after the bit was read and
+        // before the generator advances to the next state.
+        let warmup_cycles = format.size as f64 + 1.5;
+        let warmup_samples = warmup_cycles * sample_rate_hz / format.signal_frequency_hz;
+        check_format(
+            format,
+            sample_rate_hz,
+            warmup_samples.ceil() as usize,
+            num_bits,
+        );
+    }
+
+    /// Decodes the generated signal after `warmup_samples` and checks the first `num_bits` bits
+    /// (or the whole LFSR period).
+    fn check_format(
+        format: &TimecodeFormat,
+        sample_rate_hz: f64,
+        warmup_samples: usize,
+        num_bits: usize,
+    ) {
+        // The pitch detector needs a few cycles to settle after a short warmup.
+        const PITCH_SETTLING_BITS: usize = 4;
+
         let mut generator = TimecodeAudioGenerator::new(format, sample_rate_hz);
         let mut timecode = Timecode::new(format, sample_rate_hz);
         let initial_state = generator.state();
         let mut previous_timecode_state = timecode.state();
         let mut state_changed = false;
+        let mut bits_checked = 0;
         assert_eq!(timecode.state(), initial_state);
         assert_eq!(timecode.state(), generator.state());
         println!(
@@ -47,8 +617,7 @@ mod test {
             size = format.size,
         );
 
-        // Skip the first few samples until the bit detection works properly
-        for _ in 0..20 {
+        for _ in 0..warmup_samples {
             let (left, right) = generator.next_sample();
             timecode.process_channels(left, right);
         }
@@ -66,16 +635,237 @@ mod test {
                 );
 
                 assert_eq!(timecode.state(), generator.state());
+                if bits_checked >= PITCH_SETTLING_BITS {
+                    assert!((timecode.pitch() - 1.0).abs() < 0.05);
+                }
                 previous_timecode_state = timecode.state();
                 state_changed = true;
+                bits_checked += 1;
             }
 
-            if state_changed && generator.state() == initial_state {
+            if state_changed && generator.state() == initial_state || bits_checked >= num_bits {
                 break;
             }
         }
     }
 
+    #[test]
+    fn test_position_duration_conversion() {
+        let format = &SERATO_CONTROL_CD_1_0_0;
+        assert_eq!(format.position_to_duration(0), Duration::ZERO);
+        assert_eq!(format.position_to_duration(1), Duration::from_millis(1));
+        assert_eq!(
+            format.position_to_duration(1500),
+            Duration::from_millis(1500)
+        );
+
+        assert_eq!(format.duration_to_position(Duration::ZERO), 0);
+        assert_eq!(
+            format.duration_to_position(Duration::from_millis(1500)),
+            1500
+        );
+        assert_eq!(format.duration_to_position(Duration::from_micros(1400)), 1);
+        assert_eq!(format.duration_to_position(Duration::from_micros(1600)), 2);
+
+        for position in [0, 1, 12345, 1_048_574] {
+            let duration = format.position_to_duration(position);
+            assert_eq!(format.duration_to_position(duration), position);
+        }
+    }
+
+    #[test]
+    fn test_serato_control_cd_1_0_0_metadata() {
+        let format = &SERATO_CONTROL_CD_1_0_0;
+        assert_eq!(format.rpm, None);
+        assert_eq!(format.counting, Counting::Up);
+        assert_eq!(format.duration(), Duration::from_secs(950));
+        assert_eq!(format.usable_positions(), 0..940_000);
+        assert!(format.is_usable(0));
+        assert!(format.is_usable(939_999));
+        assert!(!format.is_usable(940_000));
+    }
+
+    #[test]
+    fn test_format_metadata() {
+        for info in FORMATS {
+            let format = info.format;
+            assert!(format.lead_in < format.lead_out, "{}", info.name);
+            assert!(format.lead_out <= format.length, "{}", info.name);
+            // The positions on the medium must not repeat.
+            assert!(format.length < (1 << format.size), "{}", info.name);
+            assert_eq!(
+                format.rpm.is_some(),
+                info.medium == Medium::Vinyl,
+                "{}",
+                info.name
+            );
+        }
+
+        // 7" records play faster than 12" records.
+        assert_eq!(MIXVIBES_7INCH.rpm, Some(RPM_45));
+        assert_eq!(MIXVIBES_V2.rpm, Some(RPM_33));
+    }
+
+    #[test]
+    fn test_validate() {
+        for info in FORMATS {
+            assert_eq!(info.format.validate(), Ok(()), "{}", info.name);
+        }
+
+        let format = TimecodeFormat {
+            size: 8,
+            seed: 0b0000_0001,
+            taps: 0b0001_1101,
+            length: 255,
+            lead_in: 0,
+            lead_out: 255,
+            ..SERATO_CONTROL_CD_1_0_0
+        };
+        assert_eq!(format.validate(), Ok(()));
+
+        for size in [0, 1, 32, 64] {
+            let error = TimecodeFormat {
+                size,
+                ..format.clone()
+            }
+            .validate();
+            assert_eq!(error, Err(FormatError::InvalidSize { size }));
+        }
+        assert_eq!(
+            TimecodeFormat {
+                seed: 0x100,
+                ..format.clone()
+            }
+            .validate(),
+            Err(FormatError::SeedTooLarge {
+                seed: 0x100,
+                size: 8
+            })
+        );
+        assert_eq!(
+            TimecodeFormat {
+                taps: 0x11d,
+                ..format.clone()
+            }
+            .validate(),
+            Err(FormatError::TapsTooLarge {
+                taps: 0x11d,
+                size: 8
+            })
+        );
+        assert_eq!(
+            TimecodeFormat {
+                seed: 0,
+                ..format.clone()
+            }
+            .validate(),
+            Err(FormatError::ZeroSeed)
+        );
+
+        for signal_frequency_hz in [0.0, -1000.0, f64::INFINITY] {
+            assert_eq!(
+                TimecodeFormat {
+                    signal_frequency_hz,
+                    ..format.clone()
+                }
+                .validate(),
+                Err(FormatError::InvalidSignalFrequency {
+                    signal_frequency_hz
+                })
+            );
+        }
+        let error = TimecodeFormat {
+            signal_frequency_hz: f64::NAN,
+            ..format.clone()
+        }
+        .validate();
+        assert!(
+            matches!(error, Err(FormatError::InvalidSignalFrequency { signal_frequency_hz }) if signal_frequency_hz.is_nan())
+        );
+
+        for (lead_in, lead_out, length) in [(10, 5, 200), (0, 201, 200)] {
+            assert_eq!(
+                TimecodeFormat {
+                    lead_in,
+                    lead_out,
+                    length,
+                    ..format.clone()
+                }
+                .validate(),
+                Err(FormatError::InvalidUsableRange {
+                    lead_in,
+                    lead_out,
+                    length
+                })
+            );
+        }
+        assert_eq!(
+            TimecodeFormat {
+                lead_out: 256,
+                length: 256,
+                ..format.clone()
+            }
+            .validate(),
+            Err(FormatError::LengthTooLarge {
+                length: 256,
+                size: 8
+            })
+        );
+
+        let error = TimecodeFormat {
+            taps: 0b0001_0001,
+            ..format
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(error, FormatError::NotMaximalLength { taps: 0b0001_0001 });
+        assert_eq!(
+            error.to_string(),
+            "taps 0x11 do not produce a maximal-length LFSR"
+        );
+    }
+
+    #[test]
+    fn test_find_format() {
+        for info in FORMATS {
+            assert_eq!(find_format(info.name), Some(info));
+            assert_eq!(find_format(&info.name.to_uppercase()), Some(info));
+        }
+
+        let info = find_format("traktor_a").unwrap();
+        assert_eq!(info.vendor, Vendor::NativeInstruments);
+        assert_eq!(info.medium, Medium::Vinyl);
+        assert_eq!(info.side, Some(Side::A));
+        assert_eq!(info.format, &TRAKTOR_SCRATCH_MK1_SIDE_A);
+
+        let info = find_format("serato_cd").unwrap();
+        assert_eq!(info.medium, Medium::Cd);
+        assert_eq!(info.side, None);
+        assert_eq!(info.format, &SERATO_CONTROL_CD_1_0_0);
+
+        assert_eq!(find_format("foo"), None);
+        assert_eq!(find_format(""), None);
+    }
+
+    #[test]
+    fn test_format_names_are_unique() {
+        for (i, info) in FORMATS.iter().enumerate() {
+            for other in &FORMATS[i + 1..] {
+                assert!(!info.name.eq_ignore_ascii_case(other.name));
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_from_str() {
+        assert_eq!("serato_2b".parse(), Ok(SERATO_CV02_SIDE_B));
+        assert_eq!("Pioneer_A".parse(), Ok(PIONEER_REKORDBOX_SIDE_A));
+
+        let error = "serato".parse::<TimecodeFormat>().unwrap_err();
+        assert_eq!(error.name, "serato");
+        assert_eq!(error.to_string(), "unknown timecode format: \"serato\"");
+    }
+
     #[test]
     fn test_serato_control_cd_1_0_0_44100hz() {
         test_format(&SERATO_CONTROL_CD_1_0_0, 44100.0);
@@ -85,4 +875,92 @@ mod test {
     fn test_serato_control_cd_1_0_0_48000hz() {
         test_format(&SERATO_CONTROL_CD_1_0_0, 48000.0);
     }
+
+    #[test]
+    fn test_serato_cv02_side_a_44100hz() {
+        test_format_settled(&SERATO_CV02_SIDE_A, 44100.0);
+    }
+
+    #[test]
+    fn test_serato_cv02_side_a_48000hz() {
+        test_format_settled(&SERATO_CV02_SIDE_A, 48000.0);
+    }
+
+    #[test]
+    fn test_serato_cv02_side_b_44100hz() {
+        test_format_settled(&SERATO_CV02_SIDE_B, 44100.0);
+    }
+
+    #[test]
+    fn test_serato_cv02_side_b_48000hz() {
+        test_format_settled(&SERATO_CV02_SIDE_B, 48000.0);
+    }
+
+    #[test]
+    fn test_mixvibes_v2_44100hz() {
+        test_format_settled(&MIXVIBES_V2, 44100.0);
+    }
+
+    #[test]
+    fn test_mixvibes_v2_48000hz() {
+        test_format_settled(&MIXVIBES_V2, 48000.0);
+    }
+
+    // The 7" record uses the same timecode as the V2 record, so it's sufficient to test an
+    // excerpt.
+    const MIXVIBES_7INCH_TEST_BITS: usize = 100_000;
+
+    #[test]
+    fn test_mixvibes_7inch_44100hz() {
+        test_format_settled_bits(&MIXVIBES_7INCH, 44100.0, MIXVIBES_7INCH_TEST_BITS);
+    }
+
+    #[test]
+    fn test_mixvibes_7inch_48000hz() {
+        test_format_settled_bits(&MIXVIBES_7INCH, 48000.0, MIXVIBES_7INCH_TEST_BITS);
+    }
+
+    #[test]
+    fn test_pioneer_rekordbox_side_a_44100hz() {
+        test_format_settled(&PIONEER_REKORDBOX_SIDE_A, 44100.0);
+    }
+
+    #[test]
+    fn test_pioneer_rekordbox_side_a_48000hz() {
+        test_format_settled(&PIONEER_REKORDBOX_SIDE_A, 48000.0);
+    }
+
+    #[test]
+    fn test_pioneer_rekordbox_side_b_44100hz() {
+        test_format_settled(&PIONEER_REKORDBOX_SIDE_B, 44100.0);
+    }
+
+    #[test]
+    fn test_pioneer_rekordbox_side_b_48000hz() {
+        test_format_settled(&PIONEER_REKORDBOX_SIDE_B, 48000.0);
+    }
+
+    // The LFSR period of the Traktor formats is 8 times longer than that of the other formats,
+    // so only an excerpt is tested.
+    const TRAKTOR_TEST_BITS: usize = 200_000;
+
+    #[test]
+    fn test_traktor_scratch_mk1_side_a_44100hz() {
+        test_format_settled_bits(&TRAKTOR_SCRATCH_MK1_SIDE_A, 44100.0, TRAKTOR_TEST_BITS);
+    }
+
+    #[test]
+    fn test_traktor_scratch_mk1_side_a_48000hz() {
+        test_format_settled_bits(&TRAKTOR_SCRATCH_MK1_SIDE_A, 48000.0, TRAKTOR_TEST_BITS);
+    }
+
+    #[test]
+    fn test_traktor_scratch_mk1_side_b_44100hz() {
+        test_format_settled_bits(&TRAKTOR_SCRATCH_MK1_SIDE_B, 44100.0, TRAKTOR_TEST_BITS);
+    }
+
+    #[test]
+    fn test_traktor_scratch_mk1_side_b_48000hz() {
+        test_format_settled_bits(&TRAKTOR_SCRATCH_MK1_SIDE_B, 48000.0, TRAKTOR_TEST_BITS);
+    }
 }
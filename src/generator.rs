@@ -6,7 +6,7 @@
 //
 // SPDX-License-Identifier: MPL-2.0
 
-use super::format::TimecodeFormat;
+use super::format::{Counting, FormatError, TimecodeFormat};
 use super::lfsr::FibonacciLfsr;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -14,18 +14,32 @@ pub struct TimecodeAudioGenerator {
     lfsr: FibonacciLfsr,
     sample_rate_hz: f64,
     signal_frequency_hz: f64,
+    switch_primary: bool,
+    switch_polarity: bool,
+    switch_phase: bool,
+    counting: Counting,
     previous_bit: bool,
     cycle_index: i32,
     index: i32,
 }
 
 impl TimecodeAudioGenerator {
+    /// Create a generator for the given format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the format is invalid (see [`TimecodeFormat::validate`]).
     pub fn new(format: &TimecodeFormat, sample_rate_hz: f64) -> Self {
         let TimecodeFormat {
             size,
             seed,
             taps,
             signal_frequency_hz,
+            switch_primary,
+            switch_polarity,
+            switch_phase,
+            counting,
+            ..
         } = format;
 
         let mut lfsr = FibonacciLfsr {
@@ -43,12 +57,22 @@ impl TimecodeAudioGenerator {
             lfsr,
             sample_rate_hz,
             signal_frequency_hz,
+            switch_primary: *switch_primary,
+            switch_polarity: *switch_polarity,
+            switch_phase: *switch_phase,
+            counting: *counting,
             cycle_index: 0,
             previous_bit,
             index: 0,
         }
     }
 
+    /// Create a generator for the given format, or return an error if the format is invalid.
+    pub fn try_new(format: &TimecodeFormat, sample_rate_hz: f64) -> Result<Self, FormatError> {
+        format.validate()?;
+        Ok(Self::new(format, sample_rate_hz))
+    }
+
     const SCALE_FACTOR_ZERO: f64 = 0.75;
 
     fn scale_sample(sample: f64) -> i16 {
@@ -80,11 +104,15 @@ impl TimecodeAudioGenerator {
 
         if cycle_index == self.cycle_index && cycle_position >= 0.75 {
             self.cycle_index = cycle_index + 1;
-            self.previous_bit = (self.lfsr.state >> (self.lfsr.size - 1)) & 1 == 1;
-            self.lfsr.advance();
+            self.previous_bit = self.current_bit();
+            // Formats that count down play the LFSR backwards.
+            match self.counting {
+                Counting::Up => self.lfsr.advance(),
+                Counting::Down => self.lfsr.revert(),
+            };
         }
 
-        let secondary_bit = (self.lfsr.state >> (self.lfsr.size - 1)) == 1;
+        let secondary_bit = self.current_bit();
         let primary_bit = if cycle_position >= 0.75 {
             self.previous_bit
         } else {
@@ -99,11 +127,35 @@ impl TimecodeAudioGenerator {
             secondary_sample *= cycle;
         }
 
+        // Inverting both channels shifts the phase by half a cycle, so that the bits end up in
+        // the negative half-wave of the primary channel.
+        if self.switch_polarity {
+            primary_sample = -primary_sample;
+            secondary_sample = -secondary_sample;
+        }
+
+        // Inverting the secondary channel swaps the playback direction.
+        if self.switch_phase {
+            secondary_sample = -secondary_sample;
+        }
+
         let primary_sample = Self::scale_sample(primary_sample);
         let secondary_sample = Self::scale_sample(secondary_sample);
 
         self.index += 1;
-        (primary_sample, secondary_sample)
+        if self.switch_primary {
+            (secondary_sample, primary_sample)
+        } else {
+            (primary_sample, secondary_sample)
+        }
+    }
+
+    /// Returns the bit that was shifted into the LFSR by the last step.
+    fn current_bit(&self) -> bool {
+        match self.counting {
+            Counting::Up => (self.lfsr.state >> (self.lfsr.size - 1)) & 1 == 1,
+            Counting::Down => self.lfsr.state & 1 == 1,
+        }
     }
 
     pub fn state(&self) -> u32 {
@@ -114,7 +166,7 @@ impl TimecodeAudioGenerator {
 #[cfg(test)]
 mod test {
     use super::TimecodeAudioGenerator;
-    use crate::SERATO_CONTROL_CD_1_0_0;
+    use crate::{FormatError, TimecodeFormat, SERATO_CONTROL_CD_1_0_0};
 
     #[test]
     fn test_generator() {
@@ -127,4 +179,18 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_try_new() {
+        assert!(TimecodeAudioGenerator::try_new(&SERATO_CONTROL_CD_1_0_0, 44100.0).is_ok());
+
+        let format = TimecodeFormat {
+            seed: 0,
+            ..SERATO_CONTROL_CD_1_0_0
+        };
+        assert_eq!(
+            TimecodeAudioGenerator::try_new(&format, 44100.0),
+            Err(FormatError::ZeroSeed)
+        );
+    }
 }
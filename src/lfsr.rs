@@ -194,6 +194,21 @@ impl FibonacciLfsr {
         self.state
     }
 
+    /// Returns `true` if the LFSR runs through all 2^n - 1 non-zero states before returning to
+    /// the current state.
+    ///
+    /// This clocks the register for a whole period, so it takes a while for large LFSRs.
+    pub fn has_maximal_length(&self) -> bool {
+        let max_period = bits::mask(self.size);
+        let mut lfsr = self.clone();
+        for _ in 1..max_period {
+            if lfsr.advance() == self.state {
+                return false;
+            }
+        }
+        lfsr.advance() == self.state
+    }
+
     ///// Returns the maximum period length for the register size
     //pub fn max_period(size: usize) -> usize {
     //    assert!(size < (u32::MAX as usize));
@@ -252,6 +267,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_has_maximal_length() {
+        let lfsr = FibonacciLfsr {
+            size: 8,
+            state: 1,
+            taps: 0b00011101,
+        };
+        assert!(lfsr.has_maximal_length());
+
+        // x^8 + x^4 + 1 is not primitive.
+        let lfsr = FibonacciLfsr {
+            size: 8,
+            state: 1,
+            taps: 0b00010001,
+        };
+        assert!(!lfsr.has_maximal_length());
+
+        // Without the x^0 term, the register never returns to the initial state.
+        let lfsr = FibonacciLfsr {
+            size: 8,
+            state: 1,
+            taps: 0b00011100,
+        };
+        assert!(!lfsr.has_maximal_length());
+    }
+
     #[test]
     fn test_lfsr_advance_and_revert() {
         let mut lfsr = FibonacciLfsr {
@@ -14,15 +14,28 @@
 
 mod bits;
 mod bitstream;
+mod detector;
 mod format;
 mod generator;
 mod lfsr;
 mod pitch;
+mod sample;
 mod timecode;
 mod util;
 mod visualizer;
 
-pub use format::SERATO_CONTROL_CD_1_0_0;
+pub use bitstream::{LookupTable, LookupTableError};
+pub use detector::FormatDetector;
+pub use format::{
+    find_format, Counting, FormatError, FormatInfo, Medium, Side, TimecodeFormat,
+    UnknownFormatError, Vendor, FORMATS, MIXVIBES_7INCH, MIXVIBES_V2, PIONEER_REKORDBOX_SIDE_A,
+    PIONEER_REKORDBOX_SIDE_B, RPM_33, RPM_45, SERATO_CONTROL_CD_1_0_0, SERATO_CV02_SIDE_A,
+    SERATO_CV02_SIDE_B, TRAKTOR_SCRATCH_MK1_SIDE_A, TRAKTOR_SCRATCH_MK1_SIDE_B,
+};
 pub use generator::TimecodeAudioGenerator;
-pub use timecode::Timecode;
+pub use sample::Sample;
+pub use timecode::{
+    BitEvent, BlockSummary, DecoderStatus, SignalQuality, Timecode, TimecodeDirection,
+    TimecodeEvent,
+};
 pub use visualizer::Visualizer;
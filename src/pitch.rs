@@ -10,32 +10,65 @@
 pub struct PitchDetector {
     samples_per_quarter_cycle: f64,
     samples_since_last_quarter_cycle: f64,
-    last_primary_sample: i32,
-    last_secondary_sample: i32,
+    last_primary_sample: f64,
+    last_secondary_sample: f64,
+    pitch: f64,
 }
 
 impl PitchDetector {
+    const SMOOTHING_FACTOR: f64 = 0.25;
+
     pub fn new(sample_rate_hz: f64, timecode_frequency_hz: f64) -> Self {
         let samples_per_quarter_cycle = sample_rate_hz / timecode_frequency_hz / 4.0;
 
         PitchDetector {
             samples_per_quarter_cycle,
             samples_since_last_quarter_cycle: 1.0,
-            last_primary_sample: 0,
-            last_secondary_sample: 0,
+            last_primary_sample: 0.0,
+            last_secondary_sample: 0.0,
+            pitch: 0.0,
         }
     }
 
-    pub fn update(&mut self, primary_sample: i32, secondary_sample: i32) {
+    /// Returns the absolute pitch that was detected most recently.
+    ///
+    /// A value of 1.0 means that the timecode is played at its nominal speed.
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+
+    /// Returns the number of timecode cycles per sample at the current pitch.
+    pub fn cycles_per_sample(&self) -> f64 {
+        self.pitch / (self.samples_per_quarter_cycle * 4.0)
+    }
+
+    /// Returns the number of samples since the last (interpolated) zero crossing.
+    pub fn samples_since_zero_crossing(&self) -> f64 {
+        self.samples_since_last_quarter_cycle
+    }
+
+    pub fn update(&mut self, primary_sample: f64, secondary_sample: f64) {
         self.last_primary_sample = primary_sample;
         self.last_secondary_sample = secondary_sample;
         self.samples_since_last_quarter_cycle += 1.0;
+
+        // If no zero crossing happened for longer than the current pitch would allow, the
+        // timecode is slowing down and the pitch needs to decrease accordingly. Otherwise the
+        // last pitch would be reported forever after stopping the record.
+        //
+        // Zero crossings are only detected on the first sample after the crossing, so we need
+        // to allow one additional sample before lowering the pitch.
+        let max_pitch =
+            self.samples_per_quarter_cycle / (self.samples_since_last_quarter_cycle - 1.0);
+        if self.samples_since_last_quarter_cycle > 1.0 && max_pitch < self.pitch {
+            self.pitch = max_pitch;
+        }
     }
 
     pub fn update_after_zero_crossing(
         &mut self,
-        primary_sample: i32,
-        secondary_sample: i32,
+        primary_sample: f64,
+        secondary_sample: f64,
         primary_crossed_zero: bool,
     ) -> f64 {
         // If a channel crossed zero, we now know the last sample value a (before the zero
@@ -57,18 +90,23 @@ impl PitchDetector {
         // This gives a number between 0.0 (if b is almost 0, i.e. the zero crossing is close to b)
         // and 1.0 (if a is almost 0, i.e. the zero crossing was immediately after sampling a).
         let samples_since_zero_crossing = if primary_crossed_zero {
-            let b = f64::from(primary_sample.abs());
-            b / (b + f64::from(self.last_primary_sample.abs()))
+            let b = primary_sample.abs();
+            b / (b + self.last_primary_sample.abs())
         } else {
-            let b = f64::from(secondary_sample.abs());
-            b / (b + f64::from(self.last_secondary_sample.abs()))
+            let b = secondary_sample.abs();
+            b / (b + self.last_secondary_sample.abs())
         };
 
         let samples_since_last_quarter_cycle =
             self.samples_since_last_quarter_cycle + 1.0 - samples_since_zero_crossing;
 
+        // Individual zero crossings may be detected slightly too early or too late, so the pitch
+        // is smoothed to avoid jitter.
         let pitch = self.samples_per_quarter_cycle / samples_since_last_quarter_cycle;
+        self.pitch += Self::SMOOTHING_FACTOR * (pitch - self.pitch);
+        self.last_primary_sample = primary_sample;
+        self.last_secondary_sample = secondary_sample;
         self.samples_since_last_quarter_cycle = samples_since_zero_crossing;
-        pitch
+        self.pitch
     }
 }
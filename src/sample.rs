@@ -0,0 +1,76 @@
+// Copyright (c) 2025 Jan Holthuis <holthuis.jan@gmail.com> et al.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0. If a copy
+// of the MPL was not distributed with this file, You can obtain one at
+// http://mozilla.org/MPL/2.0/.
+//
+// SPDX-License-Identifier: MPL-2.0
+
+//! Conversion of audio samples into the decoder's internal representation.
+
+/// An audio sample that can be processed by the timecode decoder.
+///
+/// Internally, the decoder works with `f64` samples in range −1.0 to 1.0, which can represent
+/// all supported sample types without loss of precision.
+pub trait Sample: Copy {
+    /// Convert the sample to a floating point value in range −1.0 to 1.0.
+    fn to_f64(self) -> f64;
+}
+
+impl Sample for i16 {
+    fn to_f64(self) -> f64 {
+        f64::from(self) / -f64::from(i16::MIN)
+    }
+}
+
+impl Sample for i32 {
+    fn to_f64(self) -> f64 {
+        f64::from(self) / -f64::from(i32::MIN)
+    }
+}
+
+impl Sample for f32 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl Sample for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sample;
+
+    #[test]
+    fn test_integer_samples() {
+        assert_eq!(0i16.to_f64(), 0.0);
+        assert_eq!(i16::MIN.to_f64(), -1.0);
+        assert!(i16::MAX.to_f64() < 1.0);
+        assert_eq!(16384i16.to_f64(), 0.5);
+
+        assert_eq!(0i32.to_f64(), 0.0);
+        assert_eq!(i32::MIN.to_f64(), -1.0);
+        assert!(i32::MAX.to_f64() < 1.0);
+        assert_eq!((1i32 << 30).to_f64(), 0.5);
+    }
+
+    #[test]
+    fn test_float_samples() {
+        assert_eq!(0.25f32.to_f64(), 0.25);
+        assert_eq!((-0.75f64).to_f64(), -0.75);
+        assert_eq!(1e-6f32.to_f64(), f64::from(1e-6f32));
+    }
+
+    #[test]
+    fn test_sample_types_are_equivalent() {
+        for sample in [i16::MIN, -1234, -1, 0, 1, 1234, i16::MAX] {
+            let expected = sample.to_f64();
+            assert_eq!((i32::from(sample) << 16).to_f64(), expected);
+            assert_eq!((f32::from(sample) / 32768.0).to_f64(), expected);
+        }
+    }
+}
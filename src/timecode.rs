@@ -7,10 +7,13 @@
 // SPDX-License-Identifier: MPL-2.0
 
 use crate::{
-    bitstream::Bitstream, format::TimecodeFormat, pitch::PitchDetector,
-    util::ExponentialWeightedMovingAverage,
+    bitstream::{Bitstream, LookupTable, LookupTableError, SoftBit},
+    format::{Counting, FormatError, TimecodeFormat},
+    pitch::PitchDetector,
+    sample::Sample,
+    util::{ExponentialWeightedMovingAverage, HighPassFilter},
 };
-use std::cmp;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WaveCycleStatus {
@@ -24,37 +27,127 @@ pub enum TimecodeDirection {
     Backwards,
 }
 
+/// The average amplitude of the bits with the same value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BitLevel {
+    amplitude: f64,
+    /// Number of bits since a bit with this value was read.
+    stale_bits: usize,
+}
+
+impl BitLevel {
+    fn new(amplitude: f64) -> Self {
+        Self {
+            amplitude,
+            stale_bits: 0,
+        }
+    }
+
+    /// Update the level with the amplitude of a bit with this value.
+    fn update(&mut self, amplitude: f64) {
+        self.amplitude += TimecodeChannel::LEVEL_SMOOTHING_FACTOR * (amplitude - self.amplitude);
+        self.stale_bits = 0;
+    }
+
+    /// Let the level decay towards the mean amplitude if no bit with this value was read for a
+    /// while.
+    fn decay(&mut self, mean_amplitude: f64) {
+        self.stale_bits += 1;
+        if self.stale_bits > TimecodeChannel::MAX_STALE_BITS {
+            self.amplitude +=
+                TimecodeChannel::LEVEL_DECAY_FACTOR * (mean_amplitude - self.amplitude);
+        }
+    }
+}
+
+/// The amplitudes of the bits read by a [`TimecodeChannel`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct BitLevels {
+    one: BitLevel,
+    zero: BitLevel,
+    /// Average amplitude of all bits.
+    mean: f64,
+    /// Number of bits that were read.
+    count: usize,
+}
+
 #[derive(Debug)]
 pub struct TimecodeChannel {
     ewma: ExponentialWeightedMovingAverage,
     wave_cycle_status: WaveCycleStatus,
-    peak_threshold: i32,
+    bit_levels: Option<BitLevels>,
 }
 
 const TIME_CONSTANT: f64 = 0.0001;
 
-const fn sample_to_i32(sample: i16) -> i32 {
-    (sample as i32) << 16
-}
-
 impl TimecodeChannel {
-    const INITIAL_PEAK_THRESHOLD: i32 = 0;
+    /// Number of bits at the start of the signal during which the 1 level is the peak amplitude.
+    ///
+    /// The amplitude may still be rising during the first cycles (e.g. when the needle is
+    /// dropped), so the levels can't be estimated from single bits yet.
+    const INITIAL_BITS: usize = 8;
+
+    /// Ratio of the 0 level to the 1 level during the first bits.
+    const INITIAL_ZERO_RATIO: f64 = 0.8;
+
+    /// Smoothing factor for the average amplitudes of both bit values.
+    const LEVEL_SMOOTHING_FACTOR: f64 = 0.1;
+
+    /// Smoothing factor for pulling a stale bit level towards the mean amplitude.
+    ///
+    /// If the amplitude suddenly changes (e.g. after a loud click or when the gain is changed),
+    /// all bits may be read with the same value, so that the level of the other value isn't
+    /// updated anymore. Letting that level decay towards the mean makes sure that the threshold
+    /// recovers in that case.
+    const LEVEL_DECAY_FACTOR: f64 = 0.05;
+
+    /// Number of bits without the same value after which a bit level is considered stale.
+    ///
+    /// This needs to be longer than the longest run of equal bits in the LFSR sequence, which is
+    /// as long as the LFSR size.
+    const MAX_STALE_BITS: usize = 32;
 
     pub fn new(sample_rate_hz: f64) -> Self {
         let ewma = ExponentialWeightedMovingAverage::new(TIME_CONSTANT, sample_rate_hz);
 
         let wave_cycle_status = WaveCycleStatus::Positive;
-        let peak_threshold = Self::INITIAL_PEAK_THRESHOLD;
 
         TimecodeChannel {
             ewma,
             wave_cycle_status,
-            peak_threshold,
+            bit_levels: None,
         }
     }
 
+    /// Returns the average amplitude of bits that were read as 1 (or 0.0 if no bits were read
+    /// yet).
+    pub fn amplitude(&self) -> f64 {
+        self.bit_levels.map_or(0.0, |levels| levels.one.amplitude)
+    }
+
+    /// Returns the average amplitude of bits that were read as 0 (or 0.0 if no bits were read
+    /// yet).
+    pub fn zero_amplitude(&self) -> f64 {
+        self.bit_levels.map_or(0.0, |levels| levels.zero.amplitude)
+    }
+
+    /// Forget the bit levels, so that they are estimated from the peak amplitude again.
+    ///
+    /// This should be called while the signal is absent, because the signal may return with a
+    /// different level.
+    pub fn reset_bit_levels(&mut self) {
+        self.bit_levels = None;
+    }
+
+    /// Returns `true` if enough bits were read to estimate the bit levels from the bits
+    /// themselves instead of the peak amplitude.
+    pub fn has_bit_levels(&self) -> bool {
+        self.bit_levels
+            .is_some_and(|levels| levels.count >= Self::INITIAL_BITS)
+    }
+
     /// Returns true if the wave has crossed zero.
-    pub fn has_crossed_zero(&self, sample: i32) -> bool {
+    pub fn has_crossed_zero(&self, sample: f64) -> bool {
         match self.wave_cycle_status {
             WaveCycleStatus::Negative => sample > self.ewma.last_output,
             WaveCycleStatus::Positive => sample < self.ewma.last_output,
@@ -62,7 +155,7 @@ impl TimecodeChannel {
     }
 
     /// Process a sample and detect zero crossing.
-    pub fn process_sample(&mut self, sample: i32) -> bool {
+    pub fn process_sample(&mut self, sample: f64) -> bool {
         let crossed_zero = self.has_crossed_zero(sample);
         if crossed_zero {
             self.wave_cycle_status = match self.wave_cycle_status {
@@ -77,46 +170,566 @@ impl TimecodeChannel {
     }
 
     /// Reads a bit from the sample and adjust the threshold.
-    pub fn bit_from_sample(&mut self, sample: i32) -> bool {
+    ///
+    /// The threshold is the midpoint between the average amplitudes of 1 and 0 bits, so it
+    /// follows changes of the signal level. The confidence of the bit depends on the distance of
+    /// the sample to the threshold, relative to the distance between threshold and the average
+    /// amplitudes.
+    pub fn bit_from_sample(&mut self, sample: f64) -> SoftBit {
         let sample = self.ewma.difference_to(sample).abs();
-        self.peak_threshold = cmp::max(sample, self.peak_threshold);
-        let threshold = (f64::from(self.peak_threshold) * 0.9).trunc() as i32;
-        sample > threshold
+        let levels = self.bit_levels.get_or_insert(BitLevels {
+            one: BitLevel::new(0.0),
+            zero: BitLevel::new(0.0),
+            mean: 0.0,
+            count: 0,
+        });
+        if levels.count < Self::INITIAL_BITS {
+            let one = levels.one.amplitude.max(sample);
+            levels.one = BitLevel::new(one);
+            levels.zero = BitLevel::new(one * Self::INITIAL_ZERO_RATIO);
+            levels.mean = (levels.one.amplitude + levels.zero.amplitude) / 2.0;
+        }
+        levels.count += 1;
+
+        let threshold = (levels.one.amplitude + levels.zero.amplitude) / 2.0;
+        let margin = (levels.one.amplitude - levels.zero.amplitude) / 2.0;
+        let value = sample > threshold;
+        let confidence = if margin > 0.0 {
+            ((sample - threshold).abs() / margin).min(1.0)
+        } else {
+            0.0
+        };
+
+        if levels.count > Self::INITIAL_BITS {
+            levels.mean += Self::LEVEL_SMOOTHING_FACTOR * (sample - levels.mean);
+            if value {
+                levels.one.update(sample);
+                levels.zero.decay(levels.mean);
+            } else {
+                levels.zero.update(sample);
+                levels.one.decay(levels.mean);
+            }
+        }
+
+        SoftBit { value, confidence }
+    }
+}
+
+/// A change of the decoder state that happened while processing a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimecodeEvent {
+    /// The signal level stayed below the noise floor or no zero crossing was detected for longer
+    /// than the signal timeout, e.g. because the needle was lifted.
+    SignalLost,
+    /// The signal is present again after it was lost.
+    SignalReturned,
+    /// The position became valid again at a place that doesn't continue the last valid
+    /// position, e.g. because the needle was dropped somewhere else on the record.
+    NeedleDrop {
+        /// The last valid position before the position was lost.
+        from: u32,
+        /// The new position.
+        to: u32,
+    },
+}
+
+/// The state of the decoder after processing a sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecoderStatus {
+    /// The current playback direction.
+    pub direction: TimecodeDirection,
+    /// The current playback speed (see [`Timecode::pitch`]).
+    pub pitch: f64,
+    /// The bit that was read from the processed sample (if any).
+    pub bit: Option<bool>,
+    /// The confidence of the bit that was read, from 0.0 (the reading was right at the decision
+    /// threshold) to 1.0.
+    pub bit_confidence: Option<f64>,
+    /// The decoded position (if the bitstream is valid).
+    pub position: Option<u32>,
+    /// The decoded position including the fraction of the current cycle (see
+    /// [`Timecode::interpolated_position`]).
+    pub interpolated_position: Option<f64>,
+    /// `true` if enough consecutive bits were read to consider the bitstream valid.
+    pub is_valid: bool,
+    /// `true` if a timecode signal is present.
+    pub signal_present: bool,
+    /// The event that happened while processing the sample (if any).
+    ///
+    /// If several events happen at the same sample, the later ones are reported with the
+    /// following samples.
+    pub event: Option<TimecodeEvent>,
+}
+
+/// A bit that was read while processing a block of samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitEvent {
+    /// The index of the frame in the block that the bit was read from.
+    pub offset: usize,
+    /// The bit value.
+    pub bit: bool,
+    /// The confidence of the bit value (see [`DecoderStatus::bit_confidence`]).
+    pub confidence: f64,
+    /// The decoded position after reading the bit (if the bitstream is valid).
+    pub position: Option<u32>,
+}
+
+/// The state of the decoder after processing a block of samples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockSummary {
+    /// The playback direction at the end of the block.
+    pub direction: TimecodeDirection,
+    /// The playback speed at the end of the block (see [`Timecode::pitch`]).
+    pub pitch: f64,
+    /// The decoded position at the end of the block (if the bitstream is valid).
+    pub position: Option<u32>,
+    /// The interpolated position at the end of the block (if the bitstream is valid).
+    pub interpolated_position: Option<f64>,
+    /// `true` if a timecode signal is present at the end of the block.
+    pub signal_present: bool,
+    /// All bits that were read from the block, in order.
+    pub bits: Vec<BitEvent>,
+    /// All events that happened while processing the block, in order, together with the index
+    /// of the frame that caused them.
+    pub events: Vec<(usize, TimecodeEvent)>,
+}
+
+impl Default for BlockSummary {
+    fn default() -> Self {
+        Self {
+            direction: TimecodeDirection::Forwards,
+            pitch: 0.0,
+            position: None,
+            interpolated_position: None,
+            signal_present: false,
+            bits: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+}
+
+/// Describes how well the timecode signal can be decoded.
+///
+/// All values are scores between 0.0 (unusable) and 1.0 (perfect).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalQuality {
+    /// Score for the amplitude of the signal.
+    pub level: f64,
+    /// Score for the amplitude difference between 1 and 0 bits.
+    pub separation: f64,
+    /// Score for the rate of bits that don't match the bits predicted by the LFSR.
+    pub bit_errors: f64,
+    /// Score for the stability of the carrier frequency.
+    pub stability: f64,
+}
+
+impl SignalQuality {
+    /// The signal level (relative to full scale) in dB where the level score is 0.0.
+    const MIN_LEVEL_DB: f64 = -50.0;
+
+    /// The signal level (relative to full scale) in dB where the level score is 1.0.
+    const GOOD_LEVEL_DB: f64 = -20.0;
+
+    /// Relative amplitude difference between 1 and 0 bits where the separation score is 1.0.
+    const GOOD_SEPARATION: f64 = 0.15;
+
+    /// Bit error rate where the bit error score is 0.0.
+    const MAX_BIT_ERROR_RATE: f64 = 0.1;
+
+    /// Average pitch change between bits where the stability score is 0.0.
+    const MAX_PITCH_JITTER: f64 = 0.1;
+
+    /// A signal that can't be decoded at all.
+    const NONE: Self = Self {
+        level: 0.0,
+        separation: 0.0,
+        bit_errors: 0.0,
+        stability: 0.0,
+    };
+
+    /// Returns the overall signal quality in percent.
+    ///
+    /// Each score limits the overall quality, i.e. the signal is only considered perfect if all
+    /// scores are perfect.
+    pub fn percent(&self) -> f64 {
+        self.level * self.separation * self.bit_errors * self.stability * 100.0
     }
 }
 
 #[derive(Debug)]
 pub struct Timecode {
+    format: TimecodeFormat,
     bitstream: Bitstream,
+    input_filters: Option<(HighPassFilter, HighPassFilter)>,
     primary_channel: TimecodeChannel,
     secondary_channel: TimecodeChannel,
     direction: TimecodeDirection,
-    pitch: PitchDetector,
+    pitch_detector: PitchDetector,
+    pitch: f64,
+    cycles_since_bit: f64,
+    sample_rate_hz: f64,
+    level: ExponentialWeightedMovingAverage,
+    noise_floor: f64,
+    samples_below_noise_floor: usize,
+    samples_above_noise_floor: usize,
+    zero_crossings_above_noise_floor: usize,
+    samples_since_zero_crossing: usize,
+    signal_timeout_samples: usize,
+    signal_return_samples: usize,
+    signal_lost: bool,
+    last_position: Option<u32>,
+    bits_since_last_position: Option<i64>,
+    relocked_bits: usize,
+    pending_event: Option<TimecodeEvent>,
+    bit_error_rate: f64,
+    bit_pitch: f64,
+    pitch_jitter: f64,
 }
 
 impl Timecode {
+    /// If the signal level stays below the noise floor or no zero crossing was detected during
+    /// this time, the signal is considered absent (see [`Timecode::set_signal_timeout`]).
+    const SIGNAL_TIMEOUT_SECS: f64 = 0.1;
+
+    /// After the signal was lost, the signal level needs to stay above the noise floor for this
+    /// time before the signal is considered present again (see
+    /// [`Timecode::set_signal_return_time`]).
+    const SIGNAL_RETURN_SECS: f64 = 0.01;
+
+    /// Number of zero crossings (i.e. 2 cycles) that need to be detected above the noise floor
+    /// before the signal is considered present again. This prevents clicks or DC offset jumps
+    /// from being mistaken for the signal.
+    const MIN_RETURN_ZERO_CROSSINGS: usize = 8;
+
+    /// Default signal level (relative to full scale) below which the input is considered to be
+    /// noise (see [`Timecode::set_noise_floor`]). This corresponds to -80 dB.
+    pub const DEFAULT_NOISE_FLOOR: f64 = 0.0001;
+
+    /// Time constant of the signal level envelope.
+    ///
+    /// The envelope is the larger amplitude of both channels, which never drops below about 70%
+    /// of the peak amplitude during a cycle because the channels are 90° out of phase. This means
+    /// that the envelope can follow the signal closely, so that the noise after lifting the
+    /// needle is ignored within a single cycle.
+    const LEVEL_TIME_CONSTANT: f64 = 0.0001;
+
+    /// Number of bit errors within one LFSR length that are corrected by default (see
+    /// [`Timecode::set_max_bit_errors`]).
+    pub const DEFAULT_MAX_BIT_ERRORS: usize = 2;
+
+    /// Maximum difference between the position after relocking and the position that was
+    /// extrapolated from the last valid position that isn't reported as needle drop. Bits may
+    /// be skipped or read twice while the signal is disturbed.
+    const NEEDLE_DROP_TOLERANCE: u64 = 4;
+
+    /// Number of bits that need to be read without errors after relocking at a different place
+    /// before the needle drop is reported.
+    const NEEDLE_DROP_CONFIRMATION_BITS: usize = 8;
+
+    /// Smoothing factor for the bit error rate and pitch jitter (see [`Timecode::signal_quality`]).
+    const QUALITY_SMOOTHING_FACTOR: f64 = 0.01;
+
+    /// Create a decoder for the given format.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the format is invalid (see [`TimecodeFormat::validate`]).
     pub fn new(format: &TimecodeFormat, sample_rate_hz: f64) -> Self {
-        let TimecodeFormat {
-            size,
-            seed,
-            taps,
-            signal_frequency_hz,
-        } = format;
-
-        let bitstream = Bitstream::new(*size, *seed, *taps);
+        let bitstream = Bitstream::new(format.size, format.seed, format.taps);
+        Self::with_bitstream(format, bitstream, sample_rate_hz)
+    }
+
+    /// Create a decoder for the given format without waiting for the position lookup table.
+    ///
+    /// Building the lookup table takes a while for large LFSRs, so it's built on a background
+    /// thread instead. Pitch and direction are available immediately, the position becomes
+    /// available as soon as the lookup table is ready (see [`Timecode::is_lookup_table_ready`]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the format is invalid (see [`TimecodeFormat::validate`]).
+    pub fn new_deferred(format: &TimecodeFormat, sample_rate_hz: f64) -> Self {
+        let bitstream = Bitstream::new_deferred(format.size, format.seed, format.taps);
+        Self::with_bitstream(format, bitstream, sample_rate_hz)
+    }
+
+    /// Create a decoder for the given format that uses a precomputed lookup table (see
+    /// [`LookupTable::read_from`]).
+    ///
+    /// Returns an error if the format is invalid or the table was built for a different format.
+    /// The format is not checked for a maximal-length LFSR (see [`TimecodeFormat::validate`]),
+    /// because that takes as long as building the table.
+    pub fn with_lookup_table(
+        format: &TimecodeFormat,
+        lookup_table: LookupTable,
+        sample_rate_hz: f64,
+    ) -> Result<Self, LookupTableError> {
+        format.validate_parameters()?;
+        if !lookup_table.matches_format(format) {
+            return Err(LookupTableError::FormatMismatch);
+        }
+        let bitstream = Bitstream::from_lookup_table(lookup_table);
+        Ok(Self::with_bitstream(format, bitstream, sample_rate_hz))
+    }
+
+    fn with_bitstream(
+        format: &TimecodeFormat,
+        mut bitstream: Bitstream,
+        sample_rate_hz: f64,
+    ) -> Self {
+        bitstream.set_max_bit_errors(Self::DEFAULT_MAX_BIT_ERRORS);
         let primary_channel = TimecodeChannel::new(sample_rate_hz);
         let secondary_channel = TimecodeChannel::new(sample_rate_hz);
 
-        let pitch = PitchDetector::new(sample_rate_hz, *signal_frequency_hz);
+        let pitch_detector = PitchDetector::new(sample_rate_hz, format.signal_frequency_hz);
 
         Self {
+            format: format.clone(),
             bitstream,
+            input_filters: None,
             primary_channel,
             secondary_channel,
             direction: TimecodeDirection::Forwards,
-            pitch,
+            pitch_detector,
+            pitch: 0.0,
+            cycles_since_bit: 0.0,
+            sample_rate_hz,
+            level: ExponentialWeightedMovingAverage::new(Self::LEVEL_TIME_CONSTANT, sample_rate_hz),
+            noise_floor: Self::DEFAULT_NOISE_FLOOR,
+            samples_below_noise_floor: 0,
+            samples_above_noise_floor: 0,
+            zero_crossings_above_noise_floor: 0,
+            samples_since_zero_crossing: 0,
+            signal_timeout_samples: (Self::SIGNAL_TIMEOUT_SECS * sample_rate_hz) as usize,
+            signal_return_samples: (Self::SIGNAL_RETURN_SECS * sample_rate_hz) as usize,
+            signal_lost: false,
+            last_position: None,
+            bits_since_last_position: None,
+            relocked_bits: 0,
+            pending_event: None,
+            bit_error_rate: 0.0,
+            bit_pitch: 0.0,
+            pitch_jitter: 0.0,
+        }
+    }
+
+    /// Create a decoder for the given format, or return an error if the format is invalid.
+    pub fn try_new(format: &TimecodeFormat, sample_rate_hz: f64) -> Result<Self, FormatError> {
+        format.validate()?;
+        Ok(Self::new(format, sample_rate_hz))
+    }
+
+    /// Like [`Timecode::new_deferred`], but returns an error if the format is invalid.
+    ///
+    /// The format is not checked for a maximal-length LFSR (see [`TimecodeFormat::validate`]),
+    /// because that takes as long as building the lookup table.
+    pub fn try_new_deferred(
+        format: &TimecodeFormat,
+        sample_rate_hz: f64,
+    ) -> Result<Self, FormatError> {
+        format.validate_parameters()?;
+        Ok(Self::new_deferred(format, sample_rate_hz))
+    }
+
+    /// Returns the current playback speed of the timecode.
+    ///
+    /// A value of 1.0 means that the timecode is played at its nominal speed, 0.5 means half
+    /// speed and 0.0 means that the record is not moving. If the timecode is played backwards,
+    /// the pitch is negative.
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+
+    /// Returns the format that is decoded.
+    pub fn format(&self) -> &TimecodeFormat {
+        &self.format
+    }
+
+    /// Returns the current playback direction of the timecode.
+    pub fn direction(&self) -> TimecodeDirection {
+        self.direction
+    }
+
+    /// Returns the decoded position.
+    ///
+    /// Returns `None` if the bitstream is considered invalid.
+    pub fn position(&self) -> Option<u32> {
+        self.bitstream.position()
+    }
+
+    /// Returns the decoded position including the fraction of the current cycle.
+    ///
+    /// The position from the bitstream only changes once per cycle, i.e. when a new bit is
+    /// read. Between bits, this extrapolates the position from the time since the (interpolated)
+    /// zero crossing where the last bit was read and the current pitch, so that it changes
+    /// smoothly with every sample.
+    ///
+    /// Returns `None` if the bitstream is considered invalid.
+    pub fn interpolated_position(&self) -> Option<f64> {
+        let position = f64::from(self.position()?);
+
+        // The next bit will be read exactly one cycle after the last one, so the extrapolated
+        // position must not overtake the next position.
+        let cycles = self.cycles_since_bit.min(1.0);
+
+        let interpolated_position = if self.is_counting_up() {
+            position + cycles
+        } else {
+            position - cycles
+        };
+        Some(interpolated_position)
+    }
+
+    /// Returns `true` if the position increases in the current playback direction, i.e. the LFSR
+    /// is clocked forwards.
+    pub(crate) fn is_counting_up(&self) -> bool {
+        (self.direction == TimecodeDirection::Forwards) == (self.format.counting == Counting::Up)
+    }
+
+    /// Returns the decoded position as time offset from the start of the timecode.
+    ///
+    /// Returns `None` if the bitstream is considered invalid.
+    pub fn time(&self) -> Option<Duration> {
+        self.position()
+            .map(|position| self.format.position_to_duration(position))
+    }
+
+    /// Returns `true` if the position lookup table is ready.
+    ///
+    /// This is always the case for decoders created with [`Timecode::new`].
+    pub fn is_lookup_table_ready(&self) -> bool {
+        self.bitstream.is_lookup_table_ready()
+    }
+
+    /// Returns `true` if enough consecutive bits were read to consider the bitstream valid.
+    pub fn is_valid(&self) -> bool {
+        self.bitstream.is_valid()
+    }
+
+    /// Returns the average amplitude of the 1 bits in the signal, relative to full scale.
+    ///
+    /// The bit threshold adapts to this level automatically, but it can be used to show the
+    /// input level to the user.
+    pub fn signal_amplitude(&self) -> f64 {
+        self.primary_channel.amplitude()
+    }
+
+    /// Returns `true` if a timecode signal is present.
+    ///
+    /// The signal is considered absent if its level stays below the noise floor or no zero
+    /// crossing is detected for longer than the signal timeout. While the signal is absent, the
+    /// pitch is zero, the position is invalid and no bits are reported. After the signal was
+    /// lost, it needs to be above the noise floor for a while before it's considered present
+    /// again (see [`Timecode::set_signal_return_time`]).
+    pub fn signal_present(&self) -> bool {
+        !self.signal_lost
+    }
+
+    /// Set the signal level (relative to full scale) below which the input is considered to be
+    /// noise, e.g. `0.001` for -60 dB.
+    ///
+    /// The default is [`Timecode::DEFAULT_NOISE_FLOOR`].
+    pub fn set_noise_floor(&mut self, noise_floor: f64) {
+        self.noise_floor = noise_floor;
+    }
+
+    /// Set how long the signal level needs to stay below the noise floor (or how long no zero
+    /// crossing needs to be detected) before the signal is considered absent.
+    ///
+    /// The default is 100 ms.
+    pub fn set_signal_timeout(&mut self, timeout: Duration) {
+        self.signal_timeout_samples = (timeout.as_secs_f64() * self.sample_rate_hz) as usize;
+    }
+
+    /// Set how long the signal level needs to stay above the noise floor (with regular zero
+    /// crossings) after the signal was lost before it's considered present again.
+    ///
+    /// Longer times ignore louder clicks and crackles while the needle is lifted, but delay
+    /// decoding after the needle was dropped. The default is 10 ms.
+    pub fn set_signal_return_time(&mut self, return_time: Duration) {
+        self.signal_return_samples = (return_time.as_secs_f64() * self.sample_rate_hz) as usize;
+    }
+
+    /// Returns the quality of the timecode signal.
+    ///
+    /// This combines the signal level, the amplitude difference between 1 and 0 bits, the rate
+    /// of bits that don't match the LFSR prediction and the stability of the carrier frequency.
+    /// Use [`SignalQuality::percent`] to get a single value that can be shown to the user.
+    pub fn signal_quality(&self) -> SignalQuality {
+        if !self.signal_present() {
+            return SignalQuality::NONE;
+        }
+
+        let amplitude = self.primary_channel.amplitude();
+        let level_db = 20.0 * amplitude.log10();
+        let level = (level_db - SignalQuality::MIN_LEVEL_DB)
+            / (SignalQuality::GOOD_LEVEL_DB - SignalQuality::MIN_LEVEL_DB);
+
+        let separation = if amplitude > 0.0 {
+            (1.0 - self.primary_channel.zero_amplitude() / amplitude)
+                / SignalQuality::GOOD_SEPARATION
+        } else {
+            0.0
+        };
+
+        let bit_errors = 1.0 - self.bit_error_rate / SignalQuality::MAX_BIT_ERROR_RATE;
+        let stability = 1.0 - self.pitch_jitter / SignalQuality::MAX_PITCH_JITTER;
+
+        SignalQuality {
+            level: level.clamp(0.0, 1.0),
+            separation: separation.clamp(0.0, 1.0),
+            bit_errors: bit_errors.clamp(0.0, 1.0),
+            stability: stability.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Set the cutoff frequency of the high-pass filter that is applied to the input before
+    /// decoding it, or disable the filter by passing `None`.
+    ///
+    /// The filter removes DC offsets and low-frequency rumble (e.g. from cheap sound cards or
+    /// turntables) that would otherwise distort the signal level and keep the signal from being
+    /// considered absent. A cutoff of a few Hz only removes DC offsets, higher cutoffs also
+    /// remove rumble. The cutoff should be well below the carrier frequency at the slowest
+    /// playback speed that needs to be decoded.
+    ///
+    /// The filter is disabled by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cutoff frequency is not positive and below half the sample rate.
+    pub fn set_high_pass_cutoff(&mut self, cutoff_hz: Option<f64>) {
+        if let Some(cutoff_hz) = cutoff_hz {
+            assert!(
+                cutoff_hz > 0.0 && cutoff_hz < self.sample_rate_hz / 2.0,
+                "invalid high-pass cutoff frequency {} Hz",
+                cutoff_hz
+            );
         }
+        self.input_filters = cutoff_hz.map(|cutoff_hz| {
+            (
+                HighPassFilter::new(cutoff_hz, self.sample_rate_hz),
+                HighPassFilter::new(cutoff_hz, self.sample_rate_hz),
+            )
+        });
+    }
+
+    /// Set the number of misread bits that are tolerated before the position is considered
+    /// invalid.
+    ///
+    /// The bits are decoded from an LFSR, so the next bit is known in advance. Bits that don't
+    /// match are corrected as long as there are no more than `max_bit_errors` errors within the
+    /// last `size` bits of the format. This prevents isolated errors (e.g. caused by dust on the
+    /// record) from invalidating the position for a whole LFSR length. Setting this to 0
+    /// disables error correction.
+    pub fn set_max_bit_errors(&mut self, max_bit_errors: usize) {
+        self.bitstream.set_max_bit_errors(max_bit_errors);
     }
+
+    /// Returns the number of bit errors that were corrected within the last `size` bits.
+    pub fn recent_bit_errors(&self) -> usize {
+        self.bitstream.recent_bit_errors()
+    }
+
     /// Returns the current state of the bitstream
     pub fn state(&self) -> u32 {
         self.bitstream.state()
@@ -126,15 +739,38 @@ impl Timecode {
         self.bitstream.set_state(state);
     }
 
-    pub fn process_channels(
+    /// Process a single sample for the left and right channel and return the current decoder
+    /// status.
+    ///
+    /// Samples can be passed in any type that implements [`Sample`], e.g. `i16` or `f32`.
+    pub fn process_channels<S: Sample>(
         &mut self,
-        primary_sample: i16,
-        secondary_sample: i16,
-    ) -> Option<(bool, Option<u32>)> {
-        let primary_sample = sample_to_i32(primary_sample);
-        let secondary_sample = sample_to_i32(secondary_sample);
+        left_sample: S,
+        right_sample: S,
+    ) -> DecoderStatus {
+        let (mut left_sample, mut right_sample) = (left_sample.to_f64(), right_sample.to_f64());
+        if let Some((left_filter, right_filter)) = &mut self.input_filters {
+            left_sample = left_filter.process(left_sample);
+            right_sample = right_filter.process(right_sample);
+        }
+        let (primary_sample, secondary_sample) = if self.format.switch_primary {
+            (right_sample, left_sample)
+        } else {
+            (left_sample, right_sample)
+        };
         let primary_crossed_zero = self.primary_channel.process_sample(primary_sample);
         let secondary_crossed_zero = self.secondary_channel.process_sample(secondary_sample);
+        let mut event = self.detect_signal_presence(
+            primary_sample,
+            secondary_sample,
+            primary_crossed_zero || secondary_crossed_zero,
+        );
+
+        // Zero crossings below the noise floor are caused by noise and would lead to spurious
+        // bits and direction changes, so they are ignored.
+        let above_noise_floor = self.samples_below_noise_floor == 0;
+        let primary_crossed_zero = primary_crossed_zero && above_noise_floor;
+        let secondary_crossed_zero = secondary_crossed_zero && above_noise_floor;
 
         // Detect the playback direction of the timecode.
         //
@@ -151,22 +787,21 @@ impl Timecode {
         //  │   │   │   │  (4)  │  wave is positive (4), the timecode is playing
         //  ╰(1)╯   ╰───╯   ╰───╯  forwards, otherwise it's playing backwards.
         //
-        if primary_crossed_zero {
-            self.direction = if self.primary_channel.wave_cycle_status
-                == self.secondary_channel.wave_cycle_status
-            {
-                TimecodeDirection::Forwards
+        // Some formats use the opposite phase relation between both channels, so that the
+        // directions are swapped.
+        if primary_crossed_zero || secondary_crossed_zero {
+            let same_status =
+                self.primary_channel.wave_cycle_status == self.secondary_channel.wave_cycle_status;
+            let forwards = if primary_crossed_zero {
+                same_status
             } else {
-                TimecodeDirection::Backwards
-            }
-        } else if secondary_crossed_zero {
-            self.direction = if self.primary_channel.wave_cycle_status
-                != self.secondary_channel.wave_cycle_status
-            {
+                !same_status
+            };
+            self.direction = if forwards != self.format.switch_phase {
                 TimecodeDirection::Forwards
             } else {
                 TimecodeDirection::Backwards
-            }
+            };
         }
 
         // The timecode has a frequency of 1000 Hz and the sample rate is 44100 Hz.
@@ -198,14 +833,19 @@ impl Timecode {
         // (i.e. per single zero crossing) then calculate:
         // pitch = 11.025 / number_of_samples_since_previous_zero_crossing
         if primary_crossed_zero || secondary_crossed_zero {
-            self.pitch.update_after_zero_crossing(
+            self.pitch_detector.update_after_zero_crossing(
                 primary_sample,
                 secondary_sample,
                 primary_crossed_zero,
             );
         } else {
-            self.pitch.update(primary_sample, secondary_sample);
+            self.pitch_detector.update(primary_sample, secondary_sample);
         }
+        self.pitch = match self.direction {
+            _ if self.signal_lost => 0.0,
+            TimecodeDirection::Forwards => self.pitch_detector.pitch(),
+            TimecodeDirection::Backwards => -self.pitch_detector.pitch(),
+        };
 
         // Read a bit from the timecode.
         //
@@ -230,18 +870,1082 @@ impl Timecode {
         // │   ╰───╯   │   │   │   │
         // ╯           ╰───╯   ╰───╯
         //
-        if secondary_crossed_zero
-            && self.primary_channel.wave_cycle_status == WaveCycleStatus::Positive
+        // Some formats encode the bits in the negative half-wave of the primary channel instead.
+        let bit_wave_cycle_status = if self.format.switch_polarity {
+            WaveCycleStatus::Negative
+        } else {
+            WaveCycleStatus::Positive
+        };
+        let bit = if secondary_crossed_zero
+            && self.primary_channel.wave_cycle_status == bit_wave_cycle_status
         {
+            let has_bit_levels = self.primary_channel.has_bit_levels();
             let bit = self.primary_channel.bit_from_sample(primary_sample);
-            if self.direction == TimecodeDirection::Forwards {
-                self.bitstream.process_bit(bit as u32);
+            // Formats that count down play the LFSR backwards during forward playback.
+            let matched = if self.is_counting_up() {
+                self.bitstream.process_soft_bit(bit)
+            } else {
+                self.bitstream.process_soft_bit_backward(bit)
+            };
+            if self.signal_lost && !has_bit_levels {
+                // The first bits after the signal returns are read from the peak amplitude and
+                // are likely wrong, so they must not become part of the resynced bitstream.
+                self.bitstream.invalidate();
+            }
+            let bit_error = if matched { 0.0 } else { 1.0 };
+            self.bit_error_rate +=
+                Self::QUALITY_SMOOTHING_FACTOR * (bit_error - self.bit_error_rate);
+            let pitch_change = (self.pitch - self.bit_pitch).abs();
+            self.pitch_jitter +=
+                Self::QUALITY_SMOOTHING_FACTOR * (pitch_change - self.pitch_jitter);
+            self.bit_pitch = self.pitch;
+            self.cycles_since_bit = self.pitch_detector.samples_since_zero_crossing()
+                * self.pitch_detector.cycles_per_sample();
+            if let Some(needle_drop) = self.detect_needle_drop() {
+                self.pending_event = Some(needle_drop);
+            }
+            Some(bit)
+        } else {
+            self.cycles_since_bit += self.pitch_detector.cycles_per_sample();
+            None
+        };
+        // Bits are already read while waiting for the signal to return, so that the bitstream
+        // can resync as early as possible, but they're only reported once the signal is present.
+        let bit = bit.filter(|_| !self.signal_lost);
+        // Only one event is reported per sample, so a needle drop that happens at the same time
+        // as the signal returns is reported with the next sample instead of being lost.
+        if event.is_none() {
+            event = self.pending_event.take();
+        }
+
+        DecoderStatus {
+            direction: self.direction,
+            pitch: self.pitch,
+            bit: bit.map(|bit| bit.value),
+            bit_confidence: bit.map(|bit| bit.confidence),
+            position: self.position(),
+            interpolated_position: self.interpolated_position(),
+            is_valid: self.is_valid(),
+            signal_present: self.signal_present(),
+            event,
+        }
+    }
+
+    /// Update the signal level and return an event if the signal was lost or returned.
+    fn detect_signal_presence(
+        &mut self,
+        primary_sample: f64,
+        secondary_sample: f64,
+        crossed_zero: bool,
+    ) -> Option<TimecodeEvent> {
+        let level = self
+            .level
+            .process(primary_sample.abs().max(secondary_sample.abs()));
+        if level < self.noise_floor {
+            self.samples_below_noise_floor = self.samples_below_noise_floor.saturating_add(1);
+            self.samples_above_noise_floor = 0;
+            self.zero_crossings_above_noise_floor = 0;
+            if self.signal_lost {
+                // The signal may return with a different level (e.g. on another record), so the
+                // bit levels need to be estimated from scratch.
+                self.primary_channel.reset_bit_levels();
+            }
+        } else {
+            self.samples_below_noise_floor = 0;
+            self.samples_above_noise_floor = self.samples_above_noise_floor.saturating_add(1);
+            if crossed_zero {
+                self.zero_crossings_above_noise_floor += 1;
+            }
+        }
+        self.samples_since_zero_crossing = if crossed_zero {
+            0
+        } else {
+            self.samples_since_zero_crossing.saturating_add(1)
+        };
+
+        let signal_present = if self.signal_lost {
+            // Short bursts above the noise floor (e.g. clicks while the needle is lifted) must
+            // not be mistaken for the signal.
+            self.samples_above_noise_floor >= self.signal_return_samples
+                && self.zero_crossings_above_noise_floor >= Self::MIN_RETURN_ZERO_CROSSINGS
+                && self.samples_since_zero_crossing < self.signal_timeout_samples
+        } else {
+            self.samples_below_noise_floor < self.signal_timeout_samples
+                && self.samples_since_zero_crossing < self.signal_timeout_samples
+        };
+        match (self.signal_lost, signal_present) {
+            (false, false) => {
+                self.signal_lost = true;
+                self.bitstream.invalidate();
+                Some(TimecodeEvent::SignalLost)
+            }
+            (true, true) => {
+                self.signal_lost = false;
+                Some(TimecodeEvent::SignalReturned)
+            }
+            _ => None,
+        }
+    }
+
+    /// Update the last valid position after reading a bit and return an event if the position
+    /// relocked at a different place.
+    fn detect_needle_drop(&mut self) -> Option<TimecodeEvent> {
+        let step = if self.is_counting_up() { 1 } else { -1 };
+        let position = self.position();
+        let Some(bits) = self.bits_since_last_position.as_mut() else {
+            match position {
+                Some(_) => self.last_position = position,
+                None if self.last_position.is_some() => {
+                    self.bits_since_last_position = Some(step);
+                    self.relocked_bits = 0;
+                }
+                None => (),
+            }
+            return None;
+        };
+
+        // Keep track of the bits that were read since the last valid position, so that relocking
+        // after a dropout isn't reported as needle drop.
+        *bits += step;
+        let expected_position = self.last_position.map(|from| i64::from(from) + *bits)?;
+        let Some(to) = position else {
+            self.relocked_bits = 0;
+            return None;
+        };
+
+        // Relocking at a different place may be caused by misread bits, so the new position
+        // needs to be read without errors for a while before it's reported.
+        let is_drop = i64::from(to).abs_diff(expected_position) > Self::NEEDLE_DROP_TOLERANCE;
+        if is_drop {
+            self.relocked_bits = if self.recent_bit_errors() == 0 {
+                self.relocked_bits + 1
+            } else {
+                0
+            };
+            if self.relocked_bits < Self::NEEDLE_DROP_CONFIRMATION_BITS {
+                return None;
+            }
+        }
+
+        let from = self.last_position.replace(to)?;
+        self.bits_since_last_position = None;
+        is_drop.then_some(TimecodeEvent::NeedleDrop { from, to })
+    }
+
+    /// Process a block of interleaved stereo samples (i.e. `[left, right, left, right, ...]`).
+    ///
+    /// This allocates a new [`BlockSummary`] for each block. Use
+    /// [`Timecode::process_interleaved_into`] to reuse its buffers instead, e.g. in an audio
+    /// callback.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of samples is odd.
+    pub fn process_interleaved<S: Sample>(&mut self, samples: &[S]) -> BlockSummary {
+        let mut summary = BlockSummary::default();
+        self.process_interleaved_into(samples, &mut summary);
+        summary
+    }
+
+    /// Like [`Timecode::process_interleaved`], but writes the result into an existing
+    /// [`BlockSummary`].
+    ///
+    /// The bits and events of the previous block are cleared, so that no memory needs to be
+    /// allocated once the buffers are large enough.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of samples is odd.
+    pub fn process_interleaved_into<S: Sample>(
+        &mut self,
+        samples: &[S],
+        summary: &mut BlockSummary,
+    ) {
+        assert_eq!(samples.len() % 2, 0, "interleaved block has an odd length");
+
+        let frames = samples.chunks_exact(2).map(|frame| (frame[0], frame[1]));
+        self.process_frames(frames, summary);
+    }
+
+    /// Process a block of samples where each channel is stored in a separate buffer.
+    ///
+    /// This allocates a new [`BlockSummary`] for each block. Use
+    /// [`Timecode::process_planar_into`] to reuse its buffers instead, e.g. in an audio
+    /// callback.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffers have different lengths.
+    pub fn process_planar<S: Sample>(&mut self, left: &[S], right: &[S]) -> BlockSummary {
+        let mut summary = BlockSummary::default();
+        self.process_planar_into(left, right, &mut summary);
+        summary
+    }
+
+    /// Like [`Timecode::process_planar`], but writes the result into an existing
+    /// [`BlockSummary`].
+    ///
+    /// The bits and events of the previous block are cleared, so that no memory needs to be
+    /// allocated once the buffers are large enough.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffers have different lengths.
+    pub fn process_planar_into<S: Sample>(
+        &mut self,
+        left: &[S],
+        right: &[S],
+        summary: &mut BlockSummary,
+    ) {
+        assert_eq!(
+            left.len(),
+            right.len(),
+            "planar buffers have different lengths"
+        );
+
+        let frames = left.iter().copied().zip(right.iter().copied());
+        self.process_frames(frames, summary);
+    }
+
+    fn process_frames<S: Sample>(
+        &mut self,
+        frames: impl Iterator<Item = (S, S)>,
+        summary: &mut BlockSummary,
+    ) {
+        summary.bits.clear();
+        summary.events.clear();
+        for (offset, (left_sample, right_sample)) in frames.enumerate() {
+            let status = self.process_channels(left_sample, right_sample);
+            if let (Some(bit), Some(confidence)) = (status.bit, status.bit_confidence) {
+                summary.bits.push(BitEvent {
+                    offset,
+                    bit,
+                    confidence,
+                    position: status.position,
+                });
+            }
+            if let Some(event) = status.event {
+                summary.events.push((offset, event));
+            }
+        }
+
+        summary.direction = self.direction;
+        summary.pitch = self.pitch;
+        summary.position = self.position();
+        summary.interpolated_position = self.interpolated_position();
+        summary.signal_present = self.signal_present();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BitEvent, BlockSummary, LookupTable, LookupTableError, Timecode, TimecodeChannel,
+        TimecodeDirection, TimecodeEvent,
+    };
+    use crate::{
+        Counting, FormatError, TimecodeAudioGenerator, TimecodeFormat, SERATO_CONTROL_CD_1_0_0,
+        TRAKTOR_SCRATCH_MK1_SIDE_A,
+    };
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        time::Duration,
+    };
+
+    const SAMPLE_RATE_HZ: f64 = 44100.0;
+
+    fn generate_samples(num_samples: usize) -> Vec<(i16, i16)> {
+        let mut generator = TimecodeAudioGenerator::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        (0..num_samples).map(|_| generator.next_sample()).collect()
+    }
+
+    /// Returns a source of white noise with the given peak-to-peak amplitude.
+    ///
+    /// This uses a simple linear congruential generator, so that the tests are deterministic.
+    fn noise_generator(amplitude: f64) -> impl FnMut() -> f64 {
+        let mut state: u32 = 1;
+        move || {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (f64::from(state >> 16) / 65536.0 - 0.5) * amplitude
+        }
+    }
+
+    #[test]
+    fn test_soft_bits() {
+        let mut channel = TimecodeChannel::new(SAMPLE_RATE_HZ);
+        let bit = channel.bit_from_sample(1.0);
+        assert!(bit.value);
+        assert_eq!(bit.confidence, 1.0);
+
+        let bit = channel.bit_from_sample(0.75);
+        assert!(!bit.value);
+        assert_eq!(bit.confidence, 1.0);
+
+        // Readings close to the threshold have a low confidence.
+        let bit = channel.bit_from_sample(0.89);
+        assert!(!bit.value);
+        assert!(bit.confidence < 0.2, "{}", bit.confidence);
+
+        let bit = channel.bit_from_sample(0.91);
+        assert!(bit.value);
+        assert!(bit.confidence < 0.2, "{}", bit.confidence);
+    }
+
+    /// Read bits that alternate between 1 and 0 with the given amplitude.
+    fn read_alternating_bits(
+        channel: &mut TimecodeChannel,
+        amplitude: f64,
+        num_bits: usize,
+    ) -> usize {
+        (0..num_bits)
+            .filter(|i| {
+                let expected = i % 2 == 0;
+                let sample = if expected {
+                    amplitude
+                } else {
+                    amplitude * 0.75
+                };
+                channel.bit_from_sample(sample).value == expected
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_bit_threshold_recovers_from_transient() {
+        let mut channel = TimecodeChannel::new(SAMPLE_RATE_HZ);
+        assert_eq!(read_alternating_bits(&mut channel, 1.0, 100), 100);
+
+        // A loud click is read as 1, but doesn't permanently raise the threshold.
+        assert!(channel.bit_from_sample(10.0).value);
+        read_alternating_bits(&mut channel, 1.0, 500);
+        assert_eq!(read_alternating_bits(&mut channel, 1.0, 100), 100);
+    }
+
+    #[test]
+    fn test_bit_threshold_follows_gain_changes() {
+        let mut channel = TimecodeChannel::new(SAMPLE_RATE_HZ);
+        read_alternating_bits(&mut channel, 1.0, 100);
+        assert!((channel.amplitude() - 1.0).abs() < 0.05);
+
+        for amplitude in [0.2, 0.5, 1.5] {
+            read_alternating_bits(&mut channel, amplitude, 1000);
+            assert_eq!(read_alternating_bits(&mut channel, amplitude, 100), 100);
+            assert!(
+                (channel.amplitude() / amplitude - 1.0).abs() < 0.05,
+                "{}",
+                channel.amplitude()
+            );
+        }
+    }
+
+    #[test]
+    fn test_pitch_forwards() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        assert_eq!(timecode.pitch(), 0.0);
+
+        for (i, (left, right)) in generate_samples(44100).into_iter().enumerate() {
+            timecode.process_channels(left, right);
+            if i > 1000 {
+                assert!(
+                    (timecode.pitch() - 1.0).abs() < 0.05,
+                    "{}",
+                    timecode.pitch()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pitch_backwards() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        // The generator fades in the signal during the first cycle, so skip that part.
+        let samples = generate_samples(44100).split_off(100);
+        for (i, (left, right)) in samples.into_iter().rev().enumerate() {
+            timecode.process_channels(left, right);
+            if i > 1000 {
+                assert!(
+                    (timecode.pitch() + 1.0).abs() < 0.05,
+                    "{}",
+                    timecode.pitch()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pitch_switched_channels() {
+        let format = &TRAKTOR_SCRATCH_MK1_SIDE_A;
+        let mut generator = TimecodeAudioGenerator::new(format, SAMPLE_RATE_HZ);
+        let samples: Vec<(i16, i16)> = (0..44100).map(|_| generator.next_sample()).collect();
+
+        let mut timecode = Timecode::new(format, SAMPLE_RATE_HZ);
+        for &(left, right) in &samples[..22050] {
+            timecode.process_channels(left, right);
+        }
+        assert_eq!(timecode.direction(), TimecodeDirection::Forwards);
+        assert!(
+            (timecode.pitch() - 1.0).abs() < 0.05,
+            "{}",
+            timecode.pitch()
+        );
+
+        for &(left, right) in samples[22050..].iter().rev() {
+            timecode.process_channels(left, right);
+        }
+        assert_eq!(timecode.direction(), TimecodeDirection::Backwards);
+        assert!(
+            (timecode.pitch() + 1.0).abs() < 0.05,
+            "{}",
+            timecode.pitch()
+        );
+    }
+
+    #[test]
+    fn test_pitch_decays_after_stop() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for (left, right) in generate_samples(4410) {
+            timecode.process_channels(left, right);
+        }
+        assert!(timecode.pitch() > 0.9);
+
+        for _ in 0..4410 {
+            timecode.process_channels(0, 0);
+        }
+        assert!(timecode.pitch() < 0.01, "{}", timecode.pitch());
+    }
+
+    #[test]
+    fn test_decoder_status() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut bits_read = 0;
+        for (left, right) in generate_samples(4410) {
+            let status = timecode.process_channels(left, right);
+            assert_eq!(status.pitch, timecode.pitch());
+            assert_eq!(status.position, timecode.position());
+            assert_eq!(status.is_valid, timecode.is_valid());
+            if status.bit.is_some() {
+                bits_read += 1;
+            }
+        }
+
+        // The timecode has a frequency of 1000 Hz, so 100 ms of audio contain 100 bits.
+        assert!((99..=101).contains(&bits_read), "{}", bits_read);
+
+        let status = timecode.process_channels(0, 0);
+        assert_eq!(status.direction, TimecodeDirection::Forwards);
+        assert!(status.signal_present);
+
+        for _ in 0..4410 {
+            timecode.process_channels(0, 0);
+        }
+        let status = timecode.process_channels(0, 0);
+        assert!(!status.signal_present);
+        assert_eq!(status.bit, None);
+    }
+
+    #[test]
+    fn test_signal_lost() {
+        let mut generator = TimecodeAudioGenerator::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for _ in 0..44100 {
+            let (left, right) = generator.next_sample();
+            let status = timecode.process_channels(left, right);
+            assert_eq!(status.event, None);
+        }
+        assert!(timecode.position().is_some());
+
+        // Lifting the needle leaves only low-level noise below the noise floor.
+        let mut noise = noise_generator(0.0001);
+        let mut events = Vec::new();
+        let mut direction = None;
+        for offset in 0..22050 {
+            let status = timecode.process_channels(noise(), noise());
+            if let Some(event) = status.event {
+                events.push(event);
+            }
+            // The signal level needs a moment to fall below the noise floor, after that the noise
+            // doesn't produce any bits or direction changes.
+            if offset >= 50 {
+                assert_eq!(status.bit, None);
+                assert_eq!(*direction.get_or_insert(status.direction), status.direction);
+            }
+            if !status.signal_present {
+                assert_eq!(status.pitch, 0.0);
+                assert_eq!(status.position, None);
+            }
+        }
+        assert_eq!(events, [TimecodeEvent::SignalLost]);
+        assert!(!timecode.signal_present());
+
+        // Putting the needle back reports the returned signal and the position recovers.
+        let mut events = Vec::new();
+        for _ in 0..44100 {
+            let (left, right) = generator.next_sample();
+            let status = timecode.process_channels(left, right);
+            if let Some(event) = status.event {
+                events.push(event);
+            }
+        }
+        assert_eq!(events, [TimecodeEvent::SignalReturned]);
+        assert!(timecode.signal_present());
+        assert!(timecode.pitch() > 0.9, "{}", timecode.pitch());
+        assert!(timecode.position().is_some());
+    }
+
+    #[test]
+    fn test_click_while_lifted() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for (left, right) in generate_samples(44100) {
+            timecode.process_channels(left, right);
+        }
+
+        // A short click while the needle is lifted must not be mistaken for the signal.
+        let mut events = Vec::new();
+        for index in 0..44100 {
+            let sample = match index {
+                20000..=20001 => 0.3,
+                20002..=20003 => -0.3,
+                _ => 0.0,
+            };
+            let status = timecode.process_channels(sample, sample);
+            if let Some(event) = status.event {
+                events.push((index, event));
+            }
+            if index >= 4410 {
+                assert!(!status.signal_present);
+                assert_eq!(status.bit, None);
+            }
+        }
+        assert_eq!(events.len(), 1, "{:?}", events);
+        assert_eq!(events[0].1, TimecodeEvent::SignalLost);
+    }
+
+    #[test]
+    fn test_signal_returns_with_different_level() {
+        let mut generator = TimecodeAudioGenerator::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for _ in 0..44100 {
+            let (left, right) = generator.next_sample();
+            timecode.process_channels(left, right);
+        }
+        for _ in 0..8820 {
+            timecode.process_channels(0, 0);
+        }
+        assert!(!timecode.signal_present());
+
+        // The bit levels of the loud signal must not be used for the quiet one, otherwise all
+        // bits are read as 0 until the levels adapt.
+        let mut samples_since_return = None;
+        for _ in 0..44100 {
+            let (left, right) = generator.next_sample();
+            let status = timecode.process_channels(left / 10, right / 10);
+            if status.event == Some(TimecodeEvent::SignalReturned) {
+                samples_since_return = Some(0);
+            }
+            if let Some(samples) = &mut samples_since_return {
+                *samples += 1;
+                if status.position.is_some() {
+                    break;
+                }
+            }
+        }
+        let samples = samples_since_return.unwrap();
+        assert!(samples < 1764, "{}", samples);
+
+        for _ in 0..4410 {
+            let (left, right) = generator.next_sample();
+            let status = timecode.process_channels(left / 10, right / 10);
+            if status.bit.is_some() {
+                assert_eq!(timecode.state(), generator.state());
+            }
+        }
+    }
+
+    #[test]
+    fn test_noise_floor() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        timecode.set_noise_floor(0.6);
+        timecode.set_signal_timeout(Duration::from_millis(10));
+
+        // The generated signal has a peak amplitude of 0.5, so it's considered noise.
+        let mut events = Vec::new();
+        for (offset, (left, right)) in generate_samples(44100).into_iter().enumerate() {
+            let status = timecode.process_channels(left, right);
+            if let Some(event) = status.event {
+                events.push((offset, event));
+            }
+            if offset >= 441 {
+                assert!(!status.signal_present);
+                assert_eq!(status.bit, None);
+            }
+        }
+        assert_eq!(events, [(440, TimecodeEvent::SignalLost)]);
+    }
+
+    #[test]
+    fn test_needle_drop() {
+        let samples = generate_samples(88200);
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for &(left, right) in &samples[..22050] {
+            let status = timecode.process_channels(left, right);
+            assert_eq!(status.event, None);
+        }
+        let from = timecode.position().unwrap();
+
+        // Skip one second of the timecode.
+        let mut events = Vec::new();
+        for &(left, right) in &samples[66150..] {
+            let status = timecode.process_channels(left, right);
+            if let Some(event) = status.event {
+                events.push(event);
+            }
+        }
+        assert_eq!(events.len(), 1, "{:?}", events);
+        let TimecodeEvent::NeedleDrop { from: old, to } = events[0] else {
+            panic!("unexpected event {:?}", events[0]);
+        };
+
+        // Misread bits are corrected for a while, so the last valid position may be slightly
+        // after the actual drop.
+        assert!((from..=from + 10).contains(&old), "{} {}", from, old);
+        assert!((from + 1000..from + 1050).contains(&to), "{} {}", from, to);
+    }
+
+    #[test]
+    fn test_needle_drop_when_signal_returns() {
+        let samples = generate_samples(88200);
+        let run = |signal_return_samples| {
+            let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+            timecode.signal_return_samples = signal_return_samples;
+            let silence = [(0, 0); 8820];
+            // Lift the needle and drop it one second later on the record.
+            let samples = samples[..22050]
+                .iter()
+                .chain(&silence)
+                .chain(&samples[66150..]);
+            let mut events = Vec::new();
+            let mut samples_above_noise_floor = 0;
+            for (index, &(left, right)) in samples.enumerate() {
+                let status = timecode.process_channels(left, right);
+                if let Some(event) = status.event {
+                    if let TimecodeEvent::NeedleDrop { .. } = event {
+                        samples_above_noise_floor = timecode.samples_above_noise_floor;
+                    }
+                    events.push((index, event));
+                }
+            }
+            (events, samples_above_noise_floor)
+        };
+
+        // With a long return time, the needle drop is detected before the signal returns.
+        let (events, samples_above_noise_floor) = run(11025);
+        assert_eq!(events.len(), 3, "{:?}", events);
+        let (index, needle_drop) = events[1];
+        assert!(matches!(needle_drop, TimecodeEvent::NeedleDrop { .. }));
+
+        // Let the signal return at the sample where the needle drop is detected.
+        let (events, _) = run(samples_above_noise_floor);
+        assert_eq!(
+            events[1..],
+            [
+                (index, TimecodeEvent::SignalReturned),
+                (index + 1, needle_drop)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_needle_drop_after_lift() {
+        let mut generator = TimecodeAudioGenerator::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut events = Vec::new();
+        for i in 0..88200 {
+            // Lift the needle while the record is stopped and put it back at the same place.
+            let (left, right) = if (22050..30870).contains(&i) {
+                (0, 0)
             } else {
-                self.bitstream.process_bit_backward(bit as u32);
+                generator.next_sample()
+            };
+            let status = timecode.process_channels(left, right);
+            if let Some(event) = status.event {
+                events.push(event);
+            }
+        }
+        assert_eq!(
+            events,
+            [TimecodeEvent::SignalLost, TimecodeEvent::SignalReturned]
+        );
+        assert!(timecode.position().is_some());
+    }
+
+    #[test]
+    fn test_counting_down() {
+        let format = TimecodeFormat {
+            counting: Counting::Down,
+            ..SERATO_CONTROL_CD_1_0_0
+        };
+        let mut generator = TimecodeAudioGenerator::new(&format, SAMPLE_RATE_HZ);
+        let mut timecode = Timecode::new(&format, SAMPLE_RATE_HZ);
+        let mut previous_position = None;
+        for index in 0..44100 {
+            let (left, right) = generator.next_sample();
+            let status = timecode.process_channels(left, right);
+            assert_eq!(status.event, None);
+            // Wait until the decoder resynced after the fade-in of the generated signal.
+            if index < 4410 {
+                continue;
+            }
+
+            // The position decreases during forward playback.
+            assert_eq!(status.direction, TimecodeDirection::Forwards);
+            let position = status.position.unwrap();
+            let interpolated_position = status.interpolated_position.unwrap();
+            assert!(interpolated_position <= f64::from(position));
+            assert!(interpolated_position >= f64::from(position) - 1.0);
+            if status.bit.is_some() {
+                assert_eq!(timecode.state(), generator.state());
+                if let Some(previous_position) = previous_position {
+                    assert_eq!(position, previous_position - 1);
+                }
+                previous_position = Some(position);
+            }
+        }
+        assert!(previous_position.is_some());
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(Timecode::try_new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ).is_ok());
+
+        let format = TimecodeFormat {
+            size: 32,
+            ..SERATO_CONTROL_CD_1_0_0
+        };
+        assert!(matches!(
+            Timecode::try_new(&format, SAMPLE_RATE_HZ),
+            Err(FormatError::InvalidSize { size: 32 })
+        ));
+    }
+
+    #[test]
+    fn test_try_new_deferred() {
+        assert!(Timecode::try_new_deferred(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ).is_ok());
+
+        let format = TimecodeFormat {
+            signal_frequency_hz: 0.0,
+            ..SERATO_CONTROL_CD_1_0_0
+        };
+        assert!(matches!(
+            Timecode::try_new_deferred(&format, SAMPLE_RATE_HZ),
+            Err(FormatError::InvalidSignalFrequency { .. })
+        ));
+    }
+
+    #[test]
+    fn test_deferred_lookup_table() {
+        let mut timecode = Timecode::new_deferred(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut generator = TimecodeAudioGenerator::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for _ in 0..SAMPLE_RATE_HZ as usize {
+            let (left, right) = generator.next_sample();
+            let status = timecode.process_channels(left, right);
+            if !timecode.is_lookup_table_ready() {
+                assert_eq!(status.position, None);
             }
-            return Some((bit, self.bitstream.position()));
         }
 
-        None
+        // Pitch detection doesn't depend on the lookup table.
+        assert!((timecode.pitch() - 1.0).abs() < 0.01);
+
+        while !timecode.is_lookup_table_ready() {
+            std::thread::yield_now();
+        }
+        assert!(timecode.position().is_some());
+    }
+
+    #[test]
+    fn test_with_lookup_table() {
+        let mut data = Vec::new();
+        LookupTable::for_format(&SERATO_CONTROL_CD_1_0_0)
+            .write_to(&mut data)
+            .unwrap();
+        let table = LookupTable::read_from(data.as_slice()).unwrap();
+
+        assert!(matches!(
+            Timecode::with_lookup_table(&TRAKTOR_SCRATCH_MK1_SIDE_A, table.clone(), SAMPLE_RATE_HZ),
+            Err(LookupTableError::FormatMismatch)
+        ));
+
+        // The table matches, but the format is still invalid.
+        let format = TimecodeFormat {
+            signal_frequency_hz: 0.0,
+            ..SERATO_CONTROL_CD_1_0_0
+        };
+        assert!(matches!(
+            Timecode::with_lookup_table(&format, table.clone(), SAMPLE_RATE_HZ),
+            Err(LookupTableError::InvalidFormat(
+                FormatError::InvalidSignalFrequency { .. }
+            ))
+        ));
+
+        let mut timecode =
+            Timecode::with_lookup_table(&SERATO_CONTROL_CD_1_0_0, table, SAMPLE_RATE_HZ).unwrap();
+        assert!(timecode.is_lookup_table_ready());
+        for (left, right) in generate_samples(4410) {
+            timecode.process_channels(left, right);
+        }
+        assert!(timecode.position().is_some());
+    }
+
+    #[test]
+    fn test_signal_quality() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        assert_eq!(timecode.signal_quality().percent(), 0.0);
+
+        for (left, right) in generate_samples(44100) {
+            timecode.process_channels(left, right);
+        }
+        let quality = timecode.signal_quality();
+        assert!(quality.percent() > 90.0, "{:?}", quality);
+
+        // There's no quality without a signal.
+        for _ in 0..8820 {
+            timecode.process_channels(0i16, 0i16);
+        }
+        assert_eq!(timecode.signal_quality().percent(), 0.0);
+    }
+
+    #[test]
+    fn test_signal_quality_noisy() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut noise = noise_generator(0.2);
+        for (left, right) in generate_samples(44100) {
+            let left = f64::from(left) / 32768.0 + noise();
+            let right = f64::from(right) / 32768.0 + noise();
+            timecode.process_channels(left, right);
+        }
+        let quality = timecode.signal_quality();
+        assert!(quality.percent() < 50.0, "{:?}", quality);
+        assert!(quality.bit_errors < 1.0, "{:?}", quality);
+    }
+
+    #[test]
+    fn test_time() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for (left, right) in generate_samples(44100) {
+            let status = timecode.process_channels(left, right);
+            let expected_time = status
+                .position
+                .map(|position| Duration::from_millis(position.into()));
+            assert_eq!(timecode.time(), expected_time);
+        }
+        assert!(timecode.time().is_some());
+    }
+
+    fn test_interpolated_position(samples: impl Iterator<Item = (i16, i16)>, pitch: f64) {
+        // At full speed, a cycle takes 44.1 samples.
+        let expected_increment = pitch / 44.1;
+
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut previous_position = None;
+        let mut increments = vec![];
+        for (i, (left, right)) in samples.enumerate() {
+            let status = timecode.process_channels(left, right);
+            let position = status.interpolated_position;
+            if let (Some(previous), Some(current)) = (previous_position, position) {
+                if i > 1000 {
+                    increments.push(current - previous);
+                }
+            }
+
+            if let (Some(position), Some(interpolated)) = (status.position, position) {
+                assert!((interpolated - f64::from(position)).abs() <= 1.0);
+            }
+            previous_position = position;
+        }
+
+        // The position needs to move smoothly in playback direction without any jumps.
+        for &increment in &increments {
+            let relative_increment: f64 = increment / expected_increment;
+            assert!(
+                (0.0..2.0).contains(&relative_increment),
+                "{}",
+                relative_increment
+            );
+        }
+
+        let mean_increment = increments.iter().sum::<f64>() / increments.len() as f64;
+        assert!((mean_increment / expected_increment - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_interpolated_position_forwards() {
+        test_interpolated_position(generate_samples(44100).into_iter(), 1.0);
+    }
+
+    #[test]
+    fn test_interpolated_position_backwards() {
+        // The generator fades in the signal during the first cycle, so skip that part.
+        let samples = generate_samples(44100).split_off(100);
+        test_interpolated_position(samples.into_iter().rev(), -1.0);
+    }
+
+    #[test]
+    fn test_process_blocks() {
+        let samples = generate_samples(44100);
+        let interleaved: Vec<i16> = samples
+            .iter()
+            .flat_map(|&(left, right)| [left, right])
+            .collect();
+        let (left, right): (Vec<i16>, Vec<i16>) = samples.iter().copied().unzip();
+
+        let mut expected = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut interleaved_timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut planar_timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+
+        const BLOCK_SIZE: usize = 512;
+        for (block_index, block) in samples.chunks(BLOCK_SIZE).enumerate() {
+            let expected_bits: Vec<BitEvent> = block
+                .iter()
+                .enumerate()
+                .filter_map(|(offset, &(left, right))| {
+                    let status = expected.process_channels(left, right);
+                    Some(BitEvent {
+                        offset,
+                        bit: status.bit?,
+                        confidence: status.bit_confidence?,
+                        position: status.position,
+                    })
+                })
+                .collect();
+
+            let start = block_index * BLOCK_SIZE;
+            let end = start + block.len();
+            let interleaved_summary =
+                interleaved_timecode.process_interleaved(&interleaved[start * 2..end * 2]);
+            let planar_summary =
+                planar_timecode.process_planar(&left[start..end], &right[start..end]);
+
+            assert_eq!(interleaved_summary, planar_summary);
+            assert_eq!(interleaved_summary.bits, expected_bits);
+            assert_eq!(interleaved_summary.pitch, expected.pitch());
+            assert_eq!(interleaved_summary.position, expected.position());
+            assert_eq!(interleaved_summary.direction, expected.direction());
+        }
+    }
+
+    #[test]
+    fn test_process_blocks_into() {
+        let interleaved: Vec<i16> = generate_samples(44100)
+            .into_iter()
+            .flat_map(|(left, right)| [left, right])
+            .collect();
+
+        let mut expected = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+
+        const BLOCK_SIZE: usize = 512;
+        let mut summary = BlockSummary {
+            bits: Vec::with_capacity(BLOCK_SIZE),
+            events: Vec::with_capacity(BLOCK_SIZE),
+            ..BlockSummary::default()
+        };
+        let bits_buffer = summary.bits.as_ptr();
+        let events_buffer = summary.events.as_ptr();
+        for block in interleaved.chunks(BLOCK_SIZE * 2) {
+            timecode.process_interleaved_into(block, &mut summary);
+            assert_eq!(summary, expected.process_interleaved(block));
+
+            // The buffers are reused instead of allocating new ones.
+            assert_eq!(summary.bits.as_ptr(), bits_buffer);
+            assert_eq!(summary.events.as_ptr(), events_buffer);
+        }
+    }
+
+    #[test]
+    fn test_float_samples() {
+        let samples = generate_samples(44100);
+        let mut timecode_i16 = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut timecode_f32 = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for (left, right) in samples {
+            let expected = timecode_i16.process_channels(left, right);
+            let status = timecode_f32
+                .process_channels(f32::from(left) / 32768.0, f32::from(right) / 32768.0);
+            assert_eq!(status, expected);
+        }
+    }
+
+    #[test]
+    fn test_quiet_float_samples() {
+        // At -60 dB, the difference between a "0" and "1" peak is only a couple of 16-bit
+        // quantization steps, but floating point samples still retain the full precision.
+        const GAIN: f64 = 0.001;
+
+        let mut generator = TimecodeAudioGenerator::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let next_sample = |generator: &mut TimecodeAudioGenerator| {
+            let (left, right) = generator.next_sample();
+            let left = (f64::from(left) / 32768.0 * GAIN) as f32;
+            let right = (f64::from(right) / 32768.0 * GAIN) as f32;
+            (left, right)
+        };
+
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for _ in 0..20 {
+            let (left, right) = next_sample(&mut generator);
+            timecode.process_channels(left, right);
+        }
+        timecode.set_state(generator.state());
+
+        for _ in 0..44100 {
+            let (left, right) = next_sample(&mut generator);
+            if timecode.process_channels(left, right).bit.is_some() {
+                assert_eq!(timecode.state(), generator.state());
+            }
+        }
+    }
+
+    #[test]
+    fn test_high_pass_filter() {
+        let mut generator = TimecodeAudioGenerator::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        timecode.set_high_pass_cutoff(Some(100.0));
+
+        // Add a strong DC offset and 10 Hz rumble (with a different phase on each channel).
+        let mut bits_read = 0;
+        for index in 0..88200 {
+            let (left, right) = generator.next_sample();
+            let angle = 2.0 * std::f64::consts::PI * 10.0 * f64::from(index) / SAMPLE_RATE_HZ;
+            let left = f64::from(left) / 32768.0 + 0.3 + 0.2 * angle.sin();
+            let right = f64::from(right) / 32768.0 - 0.2 + 0.2 * angle.cos();
+            let status = timecode.process_channels(left, right);
+            if index >= 44100 && status.bit.is_some() {
+                assert!(status.signal_present);
+                assert_eq!(timecode.state(), generator.state());
+                bits_read += 1;
+            }
+        }
+        assert!((990..=1010).contains(&bits_read), "{}", bits_read);
+        assert!(timecode.position().is_some());
+        assert!(timecode.pitch() > 0.99, "{}", timecode.pitch());
+    }
+
+    #[test]
+    fn test_invalid_high_pass_cutoff() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        for &cutoff_hz in &[0.0, -10.0, f64::NAN, f64::INFINITY, SAMPLE_RATE_HZ / 2.0] {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                timecode.set_high_pass_cutoff(Some(cutoff_hz));
+            }));
+            assert!(result.is_err(), "{}", cutoff_hz);
+        }
+
+        timecode.set_high_pass_cutoff(Some(20.0));
+        timecode.set_high_pass_cutoff(None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_process_interleaved_odd_length() {
+        let mut timecode = Timecode::new(&SERATO_CONTROL_CD_1_0_0, SAMPLE_RATE_HZ);
+        timecode.process_interleaved(&[0, 0, 0]);
     }
 }
@@ -13,7 +13,7 @@
 #[derive(Debug, Clone, PartialEq)]
 pub struct ExponentialWeightedMovingAverage {
     /// The smoothed last output.
-    pub last_output: i32,
+    pub last_output: f64,
 
     /// The smoothing factor (commonly named α in literature). Needs to be in range 0.0 − 1.0.
     /// (inclusive).
@@ -22,7 +22,7 @@ pub struct ExponentialWeightedMovingAverage {
 
 impl ExponentialWeightedMovingAverage {
     pub fn new(time_constant: f64, sample_rate_hz: f64) -> Self {
-        let last_output = 0;
+        let last_output = 0.0;
         let smoothing_factor = Self::calculate_smoothing_factor(time_constant, sample_rate_hz);
         ExponentialWeightedMovingAverage {
             last_output,
@@ -45,7 +45,7 @@ impl ExponentialWeightedMovingAverage {
     }
 
     /// Calculate the difference between the current input and last output value.
-    pub fn difference_to(&self, input: i32) -> i32 {
+    pub fn difference_to(&self, input: f64) -> f64 {
         input - self.last_output
     }
 
@@ -56,19 +56,101 @@ impl ExponentialWeightedMovingAverage {
     ///
     /// yᵢ = α ⋅ xᵢ + (1 − α) ⋅ yᵢ₋₁
     ///
-    /// To avoid unnecessary floating point calculations, the above equation can be written as:
+    /// To avoid unnecessary multiplications, the above equation can be written as:
     ///
     /// yᵢ = α ⋅ xᵢ + (1 − α) ⋅ yᵢ₋₁
     /// = α ⋅ xᵢ + yᵢ₋₁ − α ⋅ yᵢ₋₁
     /// = yᵢ₋₁ + α ⋅ xᵢ − α ⋅ yᵢ₋₁
     /// = yᵢ₋₁ + alpha ⋅ (xᵢ − yᵢ₋₁)
-    pub fn smoothen(&self, input: i32) -> i32 {
-        self.last_output + (self.smoothing_factor * self.difference_to(input) as f64) as i32
+    pub fn smoothen(&self, input: f64) -> f64 {
+        self.last_output + self.smoothing_factor * self.difference_to(input)
     }
 
     /// Calculate the next smoothed value and store it.
-    pub fn process(&mut self, input: i32) -> i32 {
+    pub fn process(&mut self, input: f64) -> f64 {
         self.last_output = self.smoothen(input);
         self.last_output
     }
 }
+
+/// Discrete-time implementation of a simple RC high-pass filter.
+///
+/// This removes DC offsets and frequencies well below the cutoff frequency from the signal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HighPassFilter {
+    last_input: f64,
+    last_output: f64,
+    smoothing_factor: f64,
+}
+
+impl HighPassFilter {
+    pub fn new(cutoff_hz: f64, sample_rate_hz: f64) -> Self {
+        HighPassFilter {
+            last_input: 0.0,
+            last_output: 0.0,
+            smoothing_factor: Self::calculate_smoothing_factor(cutoff_hz, sample_rate_hz),
+        }
+    }
+
+    /// Calculate the smoothing factor.
+    ///
+    /// Using the cutoff frequency f_c, the time constant is RC = 1/(2π ⋅ f_c). Using the time
+    /// constant and the sample rate f_s, this calculates the smoothing factor α:
+    ///
+    /// Δ_T = 1/f_s
+    /// α = RC / (RC + Δ_T)
+    ///
+    /// where Δ_T is the sampling period.
+    fn calculate_smoothing_factor(cutoff_hz: f64, sample_rate_hz: f64) -> f64 {
+        let time_constant = 1f64 / (2.0 * std::f64::consts::PI * cutoff_hz);
+        let sampling_period_secs = 1f64 / sample_rate_hz;
+        time_constant / (time_constant + sampling_period_secs)
+    }
+
+    /// Calculate the next filtered value.
+    ///
+    /// This calculates the next filtered value yᵢ using the previous filtered value yᵢ₋₁, the
+    /// current unfiltered value xᵢ, the previous unfiltered value xᵢ₋₁ and the smoothing
+    /// factor α:
+    ///
+    /// yᵢ = α ⋅ (yᵢ₋₁ + xᵢ − xᵢ₋₁)
+    pub fn process(&mut self, input: f64) -> f64 {
+        self.last_output = self.smoothing_factor * (self.last_output + input - self.last_input);
+        self.last_input = input;
+        self.last_output
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::HighPassFilter;
+
+    const SAMPLE_RATE_HZ: f64 = 44100.0;
+
+    /// Returns the peak amplitude of the filter output for the given input during the second
+    /// half of one second.
+    fn output_amplitude(filter: &mut HighPassFilter, input: impl Fn(f64) -> f64) -> f64 {
+        (0..SAMPLE_RATE_HZ as usize)
+            .map(|index| filter.process(input(index as f64 / SAMPLE_RATE_HZ)))
+            .skip(SAMPLE_RATE_HZ as usize / 2)
+            .fold(0.0, |amplitude, output| output.abs().max(amplitude))
+    }
+
+    fn sine(frequency_hz: f64) -> impl Fn(f64) -> f64 {
+        move |time| (2.0 * std::f64::consts::PI * frequency_hz * time).sin()
+    }
+
+    #[test]
+    fn test_high_pass_filter() {
+        let mut filter = HighPassFilter::new(100.0, SAMPLE_RATE_HZ);
+        assert!(output_amplitude(&mut filter, |_| 0.3) < 1e-6);
+
+        let mut filter = HighPassFilter::new(100.0, SAMPLE_RATE_HZ);
+        let amplitude = output_amplitude(&mut filter, sine(10.0));
+        assert!(amplitude < 0.11, "{}", amplitude);
+
+        let mut filter = HighPassFilter::new(100.0, SAMPLE_RATE_HZ);
+        let amplitude = output_amplitude(&mut filter, sine(1000.0));
+        assert!(amplitude > 0.98, "{}", amplitude);
+    }
+}